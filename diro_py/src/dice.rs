@@ -20,7 +20,7 @@ impl Dice {
     }
 
     fn __repr__(&self) -> String {
-        format!("{}", self.0.expr())
+        self.0.expr()
     }
 }
 
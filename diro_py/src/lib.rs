@@ -1,3 +1,6 @@
+// pyo3 0.16 macros expand `impl` blocks inside generated statics.
+#![allow(non_local_definitions)]
+
 use diro::DiroError;
 use pyo3::exceptions::{PyValueError, PyZeroDivisionError};
 use pyo3::prelude::*;
@@ -52,7 +55,7 @@ fn diro_py(_py: Python, m: &PyModule) -> PyResult<()> {
     #[pyfn(m)]
     fn parse(source: &str) -> PyResult<Diro> {
         diro::parse(source)
-            .map(|ast| Diro(ast))
+            .map(Diro)
             .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
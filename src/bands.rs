@@ -0,0 +1,94 @@
+//! Outcome bands mapping a result into named tiers.
+
+/// A table of tiers, each starting at its minimum result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bands {
+    /// Shown in the expression, e.g. `PBTA(2D6+1)`.
+    pub name: String,
+    /// `(minimum, tier name)`, sorted by minimum.
+    pub tiers: Vec<(i32, String)>,
+}
+
+/// The tier a result falls into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tier {
+    /// Position in `Bands::tiers`, from the lowest tier.
+    pub index: usize,
+    pub name: String,
+}
+
+impl Bands {
+    /// Results below the lowest minimum fall into the lowest tier.
+    pub fn new<S: Into<String>>(name: S, tiers: Vec<(i32, String)>) -> Self {
+        let mut tiers = tiers;
+        tiers.sort_by_key(|(min, _)| *min);
+        Bands {
+            name: name.into(),
+            tiers,
+        }
+    }
+
+    /// Powered by the Apocalypse moves: 6-, 7-9 and 10+.
+    pub fn pbta() -> Self {
+        Self::new(
+            "pbta",
+            vec![
+                (i32::MIN, "miss".to_string()),
+                (7, "weak hit".to_string()),
+                (10, "strong hit".to_string()),
+            ],
+        )
+    }
+
+    /// `pbta` with an extra 12+ tier for advanced moves.
+    pub fn pbta12() -> Self {
+        let mut bands = Self::pbta();
+        bands.name = "pbta12".to_string();
+        bands.tiers.push((12, "critical".to_string()));
+        bands
+    }
+
+    /// Looks a preset up by name, as used by the `pbta(...)` syntax.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "pbta" => Some(Self::pbta()),
+            "pbta12" => Some(Self::pbta12()),
+            _ => None,
+        }
+    }
+
+    pub fn tier(&self, value: i32) -> Option<Tier> {
+        let index = self
+            .tiers
+            .iter()
+            .rposition(|(min, _)| *min <= value)
+            .unwrap_or(0);
+        self.tiers.get(index).map(|(_, name)| Tier {
+            index,
+            name: name.clone(),
+        })
+    }
+}
+
+#[test]
+fn bands_test() {
+    let pbta = Bands::pbta();
+    for (value, tier) in [
+        (-1, "miss"),
+        (6, "miss"),
+        (7, "weak hit"),
+        (9, "weak hit"),
+        (10, "strong hit"),
+        (14, "strong hit"),
+    ] {
+        assert_eq!(pbta.tier(value).unwrap().name, tier, "{}", value);
+    }
+    assert_eq!(Bands::pbta12().tier(12).unwrap().index, 3);
+
+    let custom = Bands::new("", vec![(10, "high".to_string()), (5, "mid".to_string())]);
+    assert_eq!(custom.tier(1).unwrap().name, "mid");
+    assert_eq!(custom.tier(11).unwrap().name, "high");
+    assert_eq!(Bands::new("", vec![]).tier(1), None);
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind, Write};
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use diro::{parse, DiroError, Renderer};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use serde_json::json;
+
+/// Roll dice expressions, or start a REPL when no expression is given.
+#[derive(Debug, Parser)]
+#[command(name = "diro", version)]
+struct Args {
+    /// Dice expression, e.g. `3d6+2`
+    expr: Option<String>,
+    /// Roll the expression this many times
+    #[arg(short = 'n', long, default_value_t = 1)]
+    times: u32,
+    /// Seed the RNG for reproducible rolls
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Print the sampled distribution of the expression instead of rolling it
+    #[arg(long)]
+    dist: bool,
+    /// Number of samples used by `--dist`
+    #[arg(long, default_value_t = 100_000)]
+    samples: u32,
+    /// Print JSON instead of text
+    #[arg(long)]
+    json: bool,
+    /// Output format of the roll detail
+    #[arg(long, value_enum, default_value_t = Format::Plain)]
+    format: Format,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    #[error(transparent)]
+    Diro(#[from] DiroError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Readline(#[from] ReadlineError),
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Plain,
+    Markdown,
+    Html,
+    Ansi,
+    Bbcode,
+}
+
+impl Format {
+    fn renderer(self) -> &'static dyn Renderer {
+        match self {
+            Format::Plain => &diro::Plain,
+            Format::Markdown => &diro::Markdown,
+            Format::Html => &diro::Html,
+            Format::Ansi => &diro::Ansi,
+            Format::Bbcode => &diro::BBCode,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut out = io::stdout().lock();
+    let result = match &args.expr {
+        Some(expr) if args.dist => dist(&args, expr, &mut rng, &mut out),
+        Some(expr) => roll(&args, expr, &mut rng, &mut out),
+        None => repl(&args, &mut rng, &mut out),
+    }
+    .and_then(|()| out.flush().map_err(CliError::from));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // e.g. piped into `head`, which stops reading early
+        Err(CliError::Io(e)) if e.kind() == ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn roll(args: &Args, expr: &str, rng: &mut StdRng, out: &mut impl Write) -> Result<(), CliError> {
+    let mut ast = parse(expr)?;
+    for _ in 0..args.times {
+        let result = ast.eval_with(rng)?;
+        if args.json {
+            writeln!(
+                out,
+                "{}",
+                json!({
+                    "expr": ast.expr(),
+                    "detail": ast.detail_expr()?,
+                    "result": result,
+                    "tier": ast.tier()?.map(|t| t.name),
+                })
+            )?;
+        } else {
+            writeln!(out, "{}", ast.render_line(args.format.renderer())?)?;
+        }
+    }
+    Ok(())
+}
+
+fn dist(args: &Args, expr: &str, rng: &mut StdRng, out: &mut impl Write) -> Result<(), CliError> {
+    let mut ast = parse(expr)?;
+    let mut counts = BTreeMap::new();
+    let mut sum = 0i64;
+    for _ in 0..args.samples {
+        let result = ast.eval_with(rng)?;
+        *counts.entry(result).or_insert(0u32) += 1;
+        sum += result as i64;
+    }
+    let samples = args.samples.max(1) as f64;
+    let mean = sum as f64 / samples;
+    if args.json {
+        let distribution = counts
+            .iter()
+            .map(|(k, v)| (k.to_string(), json!(*v as f64 / samples)))
+            .collect::<serde_json::Map<_, _>>();
+        writeln!(
+            out,
+            "{}",
+            json!({
+                "expr": ast.expr(),
+                "samples": args.samples,
+                "mean": mean,
+                "distribution": distribution,
+            })
+        )?;
+    } else {
+        writeln!(
+            out,
+            "{} ({} samples, mean {:.2})",
+            ast.expr(),
+            args.samples,
+            mean
+        )?;
+        let max = counts.values().copied().max().unwrap_or(1) as f64;
+        for (k, v) in counts.iter() {
+            writeln!(
+                out,
+                "{:>6} {:>7.2}% {}",
+                k,
+                *v as f64 / samples * 100.0,
+                "#".repeat((*v as f64 / max * 40.0).round() as usize)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn repl(args: &Args, rng: &mut StdRng, out: &mut impl Write) -> Result<(), CliError> {
+    let mut editor = DefaultEditor::new()?;
+    let history =
+        std::env::var_os("HOME").map(|home| std::path::PathBuf::from(home).join(".diro_history"));
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+    let mut result = Ok(());
+    loop {
+        match editor.readline("diro> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let _ = editor.add_history_entry(line);
+                if line == "exit" || line == "quit" {
+                    break;
+                }
+                match roll(args, line, rng, out) {
+                    Ok(()) => {}
+                    Err(e @ CliError::Io(_)) => {
+                        result = Err(e);
+                        break;
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => {
+                result = Err(e.into());
+                break;
+            }
+        }
+    }
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    result
+}
@@ -0,0 +1,287 @@
+//! Call of Cthulhu 7e checks built on `Dice::D100`.
+
+use rand::Rng;
+
+use crate::error::{DiroError, DiroResult};
+use crate::{Dice, DiroAst, RollResult};
+
+/// Losing this much SAN in one check may cause temporary insanity.
+pub const TEMPORARY_INSANITY_LOSS: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanOutcome {
+    Success,
+    Failure,
+    /// 100, or 96-100 when SAN is below 50, loses the maximum. A loss
+    /// without a finite maximum such as `6a8` is an error.
+    Fumble,
+}
+
+/// A `.sc success/failure` sanity check against the current SAN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanCheck {
+    pub san: i32,
+    pub success: DiroAst,
+    pub failure: DiroAst,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanCheckResult {
+    pub check: RollResult,
+    pub outcome: SanOutcome,
+    /// The loss expression of the chosen branch, rolled unless fumbled.
+    pub loss: DiroAst,
+    pub lost: i32,
+    pub san: i32,
+    pub new_san: i32,
+    pub temporary_insanity: bool,
+}
+
+impl SanCheck {
+    pub fn new(san: i32, success: DiroAst, failure: DiroAst) -> Self {
+        Self {
+            san,
+            success,
+            failure,
+        }
+    }
+
+    pub fn roll(&self) -> DiroResult<SanCheckResult> {
+        self.roll_with(&mut rand::thread_rng())
+    }
+
+    pub fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> DiroResult<SanCheckResult> {
+        let check = Dice::default().roll_with(rng);
+        self.resolve(check, rng)
+    }
+
+    fn resolve<R: Rng + ?Sized>(
+        &self,
+        check: RollResult,
+        rng: &mut R,
+    ) -> DiroResult<SanCheckResult> {
+        let roll = check.result();
+        let outcome = if roll == 100 || (self.san < 50 && roll >= 96) {
+            SanOutcome::Fumble
+        } else if roll <= self.san {
+            SanOutcome::Success
+        } else {
+            SanOutcome::Failure
+        };
+        let (loss, lost) = match outcome {
+            SanOutcome::Success => {
+                let mut loss = self.success.clone();
+                let lost = loss.eval_with(rng)?;
+                (loss, lost)
+            }
+            SanOutcome::Failure => {
+                let mut loss = self.failure.clone();
+                let lost = loss.eval_with(rng)?;
+                (loss, lost)
+            }
+            SanOutcome::Fumble => match self.failure.bounds()? {
+                // open-ended dice are bounded by i32::MAX
+                (_, i32::MAX) => return Err(DiroError::Unbounded(self.failure.expr())),
+                (_, max) => (self.failure.clone(), max),
+            },
+        };
+        let lost = lost.max(0);
+        Ok(SanCheckResult {
+            check,
+            outcome,
+            loss,
+            lost,
+            san: self.san,
+            new_san: self.san.saturating_sub(lost).max(0),
+            temporary_insanity: lost >= TEMPORARY_INSANITY_LOSS,
+        })
+    }
+}
+
+impl SanCheckResult {
+    /// e.g. `D100=72/60 failure, loss 1D6=4=4, SAN 60->56`
+    pub fn detail(&self) -> DiroResult<String> {
+        let outcome = match self.outcome {
+            SanOutcome::Success => "success",
+            SanOutcome::Failure => "failure",
+            SanOutcome::Fumble => "fumble",
+        };
+        let loss = match self.outcome {
+            SanOutcome::Fumble => format!("{} max={}", self.loss.expr(), self.lost),
+            _ => format!(
+                "{}={}={}",
+                self.loss.expr(),
+                self.loss.detail_expr()?,
+                self.lost
+            ),
+        };
+        let mut s = format!(
+            "D100={}/{} {}, loss {}, SAN {}->{}",
+            self.check.result(),
+            self.san,
+            outcome,
+            loss,
+            self.san,
+            self.new_san
+        );
+        if self.temporary_insanity {
+            s.push_str(", temporary insanity");
+        }
+        Ok(s)
+    }
+}
+
+/// The improvement check of one skill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Growth {
+    pub skill: String,
+    pub before: i32,
+    pub check: RollResult,
+    /// The `1D10` gain, present when the check succeeded.
+    pub gain: Option<RollResult>,
+    pub after: i32,
+}
+
+impl Growth {
+    fn resolve<R: Rng + ?Sized>(skill: &str, value: i32, check: RollResult, rng: &mut R) -> Self {
+        let roll = check.result();
+        // above the skill, or 96+ even for skills over 95
+        let gain = if roll > value || roll > 95 {
+            Some(
+                Dice::Dice {
+                    count: 1,
+                    face: 10,
+                    kq: 0,
+                }
+                .roll_with(rng),
+            )
+        } else {
+            None
+        };
+        Growth {
+            skill: skill.to_string(),
+            before: value,
+            after: value + gain.as_ref().map_or(0, |g| g.result()),
+            check,
+            gain,
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.gain.is_some()
+    }
+
+    /// e.g. `侦查: D100=72/60 success, 1D10=4, 60->64`
+    pub fn detail(&self) -> String {
+        match &self.gain {
+            Some(gain) => format!(
+                "{}: D100={}/{} success, 1D10={}, {}->{}",
+                self.skill,
+                self.check.result(),
+                self.before,
+                gain.result(),
+                self.before,
+                self.after
+            ),
+            None => format!(
+                "{}: D100={}/{} failure, {}",
+                self.skill,
+                self.check.result(),
+                self.before,
+                self.before
+            ),
+        }
+    }
+}
+
+/// Runs the `.en` improvement check for every skill.
+pub fn grow<'a>(skills: impl IntoIterator<Item = (&'a str, i32)>) -> Vec<Growth> {
+    grow_with(skills, &mut rand::thread_rng())
+}
+
+pub fn grow_with<'a, R: Rng + ?Sized>(
+    skills: impl IntoIterator<Item = (&'a str, i32)>,
+    rng: &mut R,
+) -> Vec<Growth> {
+    skills
+        .into_iter()
+        .map(|(skill, value)| {
+            let check = Dice::default().roll_with(rng);
+            Growth::resolve(skill, value, check, rng)
+        })
+        .collect()
+}
+
+#[test]
+fn san_check_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let d100 = |tens, units| RollResult::D100(vec![([tens, units], false, vec![])]);
+    let mut rng = StdRng::seed_from_u64(0);
+    let sc = SanCheck::new(
+        45,
+        crate::parse("1").unwrap(),
+        crate::parse("1d6+1").unwrap(),
+    );
+
+    let r = sc.resolve(d100(4, 5), &mut rng).unwrap();
+    assert_eq!(r.outcome, SanOutcome::Success);
+    assert_eq!((r.lost, r.new_san), (1, 44));
+    assert_eq!(
+        r.detail().unwrap(),
+        "D100=45/45 success, loss 1=1=1, SAN 45->44"
+    );
+
+    let r = sc.resolve(d100(4, 6), &mut rng).unwrap();
+    assert_eq!(r.outcome, SanOutcome::Failure);
+    assert!((2..=7).contains(&r.lost));
+    assert_eq!(r.temporary_insanity, r.lost >= 5);
+
+    let r = sc.resolve(d100(9, 6), &mut rng).unwrap();
+    assert_eq!(r.outcome, SanOutcome::Fumble);
+    assert_eq!((r.lost, r.new_san), (7, 38));
+    assert_eq!(
+        r.detail().unwrap(),
+        "D100=96/45 fumble, loss 1D6+1 max=7, SAN 45->38, temporary insanity"
+    );
+
+    let sc = SanCheck::new(3, DiroAst::Int(0), crate::parse("1d10").unwrap());
+    let r = sc.resolve(d100(9, 9), &mut rng).unwrap();
+    assert_eq!((r.lost, r.new_san), (10, 0));
+
+    // an exploding loss has no maximum to lose on a fumble
+    for loss in ["6a8", "1d6+6a8"] {
+        let sc = SanCheck::new(45, DiroAst::Int(0), crate::parse(loss).unwrap());
+        assert!(sc.resolve(d100(4, 6), &mut rng).is_ok());
+        assert!(matches!(
+            sc.resolve(d100(9, 6), &mut rng),
+            Err(DiroError::Unbounded(_))
+        ));
+    }
+    let sc = SanCheck::new(i32::MIN, DiroAst::Int(0), crate::parse("1d6").unwrap());
+    assert_eq!(sc.resolve(d100(5, 0), &mut rng).unwrap().new_san, 0);
+}
+
+#[test]
+fn growth_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let d100 = |tens, units| RollResult::D100(vec![([tens, units], false, vec![])]);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let g = Growth::resolve("侦查", 60, d100(6, 0), &mut rng);
+    assert!(!g.success());
+    assert_eq!(g.after, 60);
+    assert_eq!(g.detail(), "侦查: D100=60/60 failure, 60");
+
+    let g = Growth::resolve("侦查", 60, d100(6, 1), &mut rng);
+    assert!(g.success());
+    assert!((61..=70).contains(&g.after));
+
+    assert!(!Growth::resolve("闪避", 97, d100(9, 5), &mut rng).success());
+    assert!(Growth::resolve("闪避", 97, d100(9, 6), &mut rng).success());
+
+    let growths = grow_with([("侦查", 60), ("聆听", 99)], &mut rng);
+    assert_eq!(growths.len(), 2);
+    assert_eq!(growths[1].skill, "聆听");
+}
@@ -0,0 +1,337 @@
+//! Dot commands in the dialect shared by most TRPG bots (`.r`, `.ra`, `.sc` ...).
+
+use crate::error::{DiroError, DiroResult};
+use crate::DiroAst;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `.r[h] [N#]<expr> [reason]`, `.rd` is `.r` with the expression `d`.
+    Roll {
+        expr: DiroAst,
+        times: u8,
+        hidden: bool,
+        reason: Option<String>,
+    },
+    /// `.ra[b|p][N] [N#][skill][value] [reason]`, `.rc` is an alias.
+    Check {
+        skill: Option<String>,
+        value: Option<i32>,
+        bp: i8,
+        times: u8,
+        reason: Option<String>,
+    },
+    /// `.sc <success>/<fail> [san] [reason]`
+    SanCheck {
+        success: DiroAst,
+        failure: DiroAst,
+        san: Option<i32>,
+        reason: Option<String>,
+    },
+    /// `.en <skill>[value] [reason]`
+    Grow {
+        skill: String,
+        value: Option<i32>,
+        reason: Option<String>,
+    },
+    /// `.coc [N]`
+    Coc(u8),
+    /// `.dnd [N]`
+    Dnd(u8),
+}
+
+/// Parses a dot command, the leading `.` may also be a full-width `。`.
+pub fn parse(source: &str) -> DiroResult<Command> {
+    let source = source.trim();
+    let body = source
+        .strip_prefix('.')
+        .or_else(|| source.strip_prefix('。'))
+        .ok_or_else(|| DiroError::UnknownCommand(source.to_string()))?;
+    let (name, rest) = split_name(body);
+    match name.to_ascii_lowercase().as_str() {
+        "r" => parse_roll(rest, false),
+        "rh" => parse_roll(rest, true),
+        "ra" | "rc" => parse_check(rest),
+        "sc" => parse_san_check(rest),
+        "en" => parse_grow(rest),
+        "coc" => parse_count(rest, "coc").map(Command::Coc),
+        "dnd" => parse_count(rest, "dnd").map(Command::Dnd),
+        _ => Err(DiroError::UnknownCommand(source.to_string())),
+    }
+}
+
+/// Splits the longest known command name off the body.
+fn split_name(body: &str) -> (&str, &str) {
+    const NAMES: [&str; 7] = ["coc", "dnd", "rh", "ra", "rc", "sc", "en"];
+    for name in NAMES {
+        if body.len() >= name.len()
+            && body.is_char_boundary(name.len())
+            && body[..name.len()].eq_ignore_ascii_case(name)
+        {
+            return body.split_at(name.len());
+        }
+    }
+    if body.starts_with(['r', 'R']) {
+        return body.split_at(1);
+    }
+    (body, "")
+}
+
+fn reason(s: &str) -> Option<String> {
+    let s = s.trim();
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+/// Splits a leading `N#` repeat count, which must be at least 1.
+fn split_times<'a>(s: &'a str, command: &str) -> DiroResult<(u8, &'a str)> {
+    let s = s.trim_start();
+    let digits = s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && s[digits..].starts_with('#') {
+        match s[..digits].parse()? {
+            0 => Err(DiroError::InvalidCommand(command.to_string())),
+            times => Ok((times, &s[digits + 1..])),
+        }
+    } else {
+        Ok((1, s))
+    }
+}
+
+/// Splits a leading unsigned number.
+fn split_int(s: &str) -> DiroResult<(Option<i32>, &str)> {
+    let s = s.trim_start();
+    let digits = s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 {
+        Ok((Some(s[..digits].parse()?), &s[digits..]))
+    } else {
+        Ok((None, s))
+    }
+}
+
+/// Parses the longest prefix that is a valid dice expression,
+/// `None` if the text doesn't look like an expression at all.
+fn split_expr(s: &str) -> DiroResult<Option<(DiroAst, &str)>> {
+    let s = s.trim_start();
+    let mut err = None;
+    for end in (1..=s.len()).rev().filter(|i| s.is_char_boundary(*i)) {
+        match crate::parse(&s[..end]) {
+            Ok(ast) => return Ok(Some((ast, &s[end..]))),
+            Err(e) => {
+                err.get_or_insert(e);
+            }
+        }
+    }
+    match err {
+        Some(e) if s.starts_with(|c: char| c.is_ascii_digit() || "-(（".contains(c)) => Err(e),
+        _ => Ok(None),
+    }
+}
+
+fn parse_roll(rest: &str, hidden: bool) -> DiroResult<Command> {
+    let (times, rest) = split_times(rest, "r")?;
+    // `.r reason` rolls the default dice
+    let (expr, rest) = split_expr(rest)?.unwrap_or((crate::parse("")?, rest));
+    Ok(Command::Roll {
+        expr,
+        times,
+        hidden,
+        reason: reason(rest),
+    })
+}
+
+fn parse_check(rest: &str) -> DiroResult<Command> {
+    let mut bp = 0;
+    let mut rest = rest;
+    while let Some(c) = rest
+        .chars()
+        .next()
+        .filter(|c| matches!(c, 'b' | 'B' | 'p' | 'P'))
+    {
+        // the count must be attached, `.rap 50` is a penalty check against 50
+        let (n, r) = match rest[1..].starts_with(char::is_whitespace) {
+            true => (None, &rest[1..]),
+            false => split_int(&rest[1..])?,
+        };
+        let n: i8 = n
+            .unwrap_or(1)
+            .try_into()
+            .map_err(|_| DiroError::InvalidCommand("ra".to_string()))?;
+        bp += if c.eq_ignore_ascii_case(&'b') { n } else { -n };
+        rest = r;
+    }
+    let (times, rest) = split_times(rest, "ra")?;
+    let (skill, value, rest) = split_skill(rest)?;
+    Ok(Command::Check {
+        skill,
+        value,
+        bp,
+        times,
+        reason: reason(rest),
+    })
+}
+
+/// Splits `skill value`, where the skill ends at a digit or whitespace.
+fn split_skill(s: &str) -> DiroResult<(Option<String>, Option<i32>, &str)> {
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+        .unwrap_or(s.len());
+    let (value, rest) = split_int(&s[end..])?;
+    Ok((reason(&s[..end]), value, rest))
+}
+
+fn parse_san_check(rest: &str) -> DiroResult<Command> {
+    let invalid = || DiroError::InvalidCommand("sc".to_string());
+    let (success, rest) = rest.trim_start().split_once('/').ok_or_else(invalid)?;
+    if success.trim().is_empty() {
+        return Err(invalid());
+    }
+    let success = crate::parse(success)?;
+    let (failure, rest) = split_expr(rest)?.ok_or_else(invalid)?;
+    let (san, rest) = split_int(rest)?;
+    Ok(Command::SanCheck {
+        success,
+        failure,
+        san,
+        reason: reason(rest),
+    })
+}
+
+fn parse_grow(rest: &str) -> DiroResult<Command> {
+    let (skill, value, rest) = split_skill(rest)?;
+    Ok(Command::Grow {
+        skill: skill.ok_or_else(|| DiroError::InvalidCommand("en".to_string()))?,
+        value,
+        reason: reason(rest),
+    })
+}
+
+fn parse_count(rest: &str, name: &str) -> DiroResult<u8> {
+    let invalid = || DiroError::InvalidCommand(name.to_string());
+    match split_int(rest)? {
+        (count, rest) if rest.trim().is_empty() => {
+            count.unwrap_or(1).try_into().map_err(|_| invalid())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[test]
+fn command_test() {
+    use crate::Dice;
+
+    let d100 = || crate::parse("").unwrap();
+    let data = [
+        (
+            ".r",
+            Command::Roll {
+                expr: d100(),
+                times: 1,
+                hidden: false,
+                reason: None,
+            },
+        ),
+        (
+            "。rd 调查",
+            Command::Roll {
+                expr: d100(),
+                times: 1,
+                hidden: false,
+                reason: Some("调查".to_string()),
+            },
+        ),
+        (
+            ".r 3#1d6+2 伤害",
+            Command::Roll {
+                expr: crate::parse("1d6+2").unwrap(),
+                times: 3,
+                hidden: false,
+                reason: Some("伤害".to_string()),
+            },
+        ),
+        (
+            ".rh4d6k3",
+            Command::Roll {
+                expr: DiroAst::Dice(Dice::dice(4, 6, 3).unwrap(), None),
+                times: 1,
+                hidden: true,
+                reason: None,
+            },
+        ),
+        (
+            ".r 潜行",
+            Command::Roll {
+                expr: d100(),
+                times: 1,
+                hidden: false,
+                reason: Some("潜行".to_string()),
+            },
+        ),
+        (
+            ".ra 侦查 60",
+            Command::Check {
+                skill: Some("侦查".to_string()),
+                value: Some(60),
+                bp: 0,
+                times: 1,
+                reason: None,
+            },
+        ),
+        (
+            ".rab2 2#侦查60 找线索",
+            Command::Check {
+                skill: Some("侦查".to_string()),
+                value: Some(60),
+                bp: 2,
+                times: 2,
+                reason: Some("找线索".to_string()),
+            },
+        ),
+        (
+            ".rcp 50",
+            Command::Check {
+                skill: None,
+                value: Some(50),
+                bp: -1,
+                times: 1,
+                reason: None,
+            },
+        ),
+        (
+            ".sc 1/1d6 60 看到尸体",
+            Command::SanCheck {
+                success: DiroAst::Int(1),
+                failure: DiroAst::Dice(Dice::dice(1, 6, 0).unwrap(), None),
+                san: Some(60),
+                reason: Some("看到尸体".to_string()),
+            },
+        ),
+        (
+            ".en 侦查",
+            Command::Grow {
+                skill: "侦查".to_string(),
+                value: None,
+                reason: None,
+            },
+        ),
+        (".coc 5", Command::Coc(5)),
+        (".DND", Command::Dnd(1)),
+    ];
+    for (input, expected) in data.iter() {
+        assert_eq!(parse(input).unwrap(), *expected, "{}", input);
+    }
+    for input in [
+        "r3d6",
+        ".st 力量50",
+        ".sc 1d6",
+        ".en",
+        ".coc x",
+        ".r (3d6",
+        ".r 0#d",
+        ".ra 0#侦查60",
+    ] {
+        assert!(parse(input).is_err(), "{}", input);
+    }
+}
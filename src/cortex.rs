@@ -0,0 +1,68 @@
+//! Cortex Prime pools of mixed dice.
+
+/// The effect die when no die is left after picking the total.
+pub const DEFAULT_EFFECT: u16 = 4;
+
+/// Which dice of a rolled pool make the total and the effect die, given as
+/// indexes into the `(face, value)` pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CortexSelection {
+    pub total: Vec<usize>,
+    /// `None` when no die is left, the effect die is then a d4.
+    pub effect: Option<usize>,
+    /// Dice that rolled a 1, they can't be picked.
+    pub hitches: Vec<usize>,
+}
+
+impl CortexSelection {
+    /// Picks the best total of `keep` dice, then the largest remaining die
+    /// as the effect die. Ties in the total leave the larger dice for the
+    /// effect.
+    pub fn best(pool: &[(u16, i32)], keep: usize) -> Self {
+        let hitches = (0..pool.len())
+            .filter(|i| pool[*i].1 == 1)
+            .collect::<Vec<_>>();
+        let mut order = (0..pool.len())
+            .filter(|i| pool[*i].1 != 1)
+            .collect::<Vec<_>>();
+        order.sort_by_key(|i| (-pool[*i].1, pool[*i].0));
+        let rest = order.split_off(keep.min(order.len()));
+        let effect = rest.into_iter().max_by_key(|i| pool[*i].0);
+        CortexSelection {
+            total: order,
+            effect,
+            hitches,
+        }
+    }
+
+    pub fn total(&self, pool: &[(u16, i32)]) -> i32 {
+        self.total.iter().map(|i| pool[*i].1).sum()
+    }
+
+    pub fn effect_die(&self, pool: &[(u16, i32)]) -> u16 {
+        self.effect.map_or(DEFAULT_EFFECT, |i| pool[i].0)
+    }
+
+    /// Every die rolled a 1.
+    pub fn botch(&self, pool: &[(u16, i32)]) -> bool {
+        self.hitches.len() == pool.len()
+    }
+}
+
+#[test]
+fn cortex_test() {
+    let pool = [(8, 5), (6, 5), (6, 1), (10, 3), (12, 5)];
+    let s = CortexSelection::best(&pool, 2);
+    assert_eq!(s.total, vec![1, 0]);
+    assert_eq!(s.total(&pool), 10);
+    assert_eq!(s.effect_die(&pool), 12);
+    assert_eq!(s.hitches, vec![2]);
+    assert!(!s.botch(&pool));
+
+    let pool = [(8, 1), (6, 4)];
+    let s = CortexSelection::best(&pool, 2);
+    assert_eq!((s.total(&pool), s.effect_die(&pool)), (4, DEFAULT_EFFECT));
+
+    let pool = [(8, 1), (6, 1)];
+    assert!(CortexSelection::best(&pool, 2).botch(&pool));
+}
@@ -0,0 +1,302 @@
+//! Card decks drawn without replacement, e.g. `draw(init)` for initiative.
+
+use std::collections::BTreeMap;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::error::{DiroError, DiroResult};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Card {
+    pub name: String,
+    /// The value a drawn card counts as in an expression.
+    pub value: i32,
+}
+
+/// Where every card of a deck is, as indexes into `Deck::cards`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeckState {
+    /// The top card is the last one.
+    pub draw_pile: Vec<usize>,
+    /// Drawn and not discarded yet.
+    pub hand: Vec<usize>,
+    pub discard: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DeckData"))]
+pub struct Deck {
+    pub name: String,
+    cards: Vec<Card>,
+    state: DeckState,
+}
+
+/// A deserialized deck, checked by `Deck::set_state` before it is used.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DeckData {
+    name: String,
+    cards: Vec<Card>,
+    state: DeckState,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DeckData> for Deck {
+    type Error = DiroError;
+
+    fn try_from(data: DeckData) -> DiroResult<Self> {
+        let mut deck = Deck::new(data.name, data.cards);
+        deck.set_state(data.state)?;
+        Ok(deck)
+    }
+}
+
+/// Named decks that expressions draw from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decks(BTreeMap<String, Deck>);
+
+impl Card {
+    pub fn new<S: Into<String>>(name: S, value: i32) -> Self {
+        Card {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+impl Deck {
+    /// An unshuffled deck, the first card is at the bottom.
+    pub fn new<S: Into<String>>(name: S, cards: Vec<Card>) -> Self {
+        let state = DeckState {
+            draw_pile: (0..cards.len()).rev().collect(),
+            ..Default::default()
+        };
+        Deck {
+            name: name.into(),
+            cards,
+            state,
+        }
+    }
+
+    /// 52 cards valued 2-14 with the ace high, and two jokers valued 15.
+    pub fn standard<S: Into<String>>(name: S) -> Self {
+        const RANKS: [&str; 13] = [
+            "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A",
+        ];
+        let mut cards = vec![];
+        for (value, rank) in (2..).zip(RANKS) {
+            for suit in ['♣', '♦', '♥', '♠'] {
+                cards.push(Card::new(format!("{}{}", rank, suit), value));
+            }
+        }
+        cards.push(Card::new("Black Joker", 15));
+        cards.push(Card::new("Red Joker", 15));
+        Deck::new(name, cards)
+    }
+
+    /// The 22 major arcana valued 0-21, and 56 minor arcana valued 1-14.
+    pub fn tarot<S: Into<String>>(name: S) -> Self {
+        const MAJOR: [&str; 22] = [
+            "The Fool",
+            "The Magician",
+            "The High Priestess",
+            "The Empress",
+            "The Emperor",
+            "The Hierophant",
+            "The Lovers",
+            "The Chariot",
+            "Strength",
+            "The Hermit",
+            "Wheel of Fortune",
+            "Justice",
+            "The Hanged Man",
+            "Death",
+            "Temperance",
+            "The Devil",
+            "The Tower",
+            "The Star",
+            "The Moon",
+            "The Sun",
+            "Judgement",
+            "The World",
+        ];
+        const RANKS: [&str; 14] = [
+            "Ace", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Page",
+            "Knight", "Queen", "King",
+        ];
+        let mut cards = (0..)
+            .zip(MAJOR)
+            .map(|(v, n)| Card::new(n, v))
+            .collect::<Vec<_>>();
+        for suit in ["Wands", "Cups", "Swords", "Pentacles"] {
+            for (value, rank) in (1..).zip(RANKS) {
+                cards.push(Card::new(format!("{} of {}", rank, suit), value));
+            }
+        }
+        Deck::new(name, cards)
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn state(&self) -> &DeckState {
+        &self.state
+    }
+
+    /// Restores a state saved by `state`, every card must be in exactly one
+    /// pile.
+    pub fn set_state(&mut self, state: DeckState) -> DiroResult<()> {
+        let mut seen = vec![false; self.cards.len()];
+        for i in state
+            .draw_pile
+            .iter()
+            .chain(state.hand.iter())
+            .chain(state.discard.iter())
+        {
+            match seen.get_mut(*i) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return Err(DiroError::InvalidDeckState(self.name.clone())),
+            }
+        }
+        if seen.contains(&false) {
+            return Err(DiroError::InvalidDeckState(self.name.clone()));
+        }
+        self.state = state;
+        Ok(())
+    }
+
+    /// Cards left in the draw pile.
+    pub fn remaining(&self) -> usize {
+        self.state.draw_pile.len()
+    }
+
+    /// Shuffles the draw pile, drawn and discarded cards stay out.
+    pub fn shuffle(&mut self) {
+        self.shuffle_with(&mut rand::thread_rng())
+    }
+
+    /// Like `shuffle`, but shuffles with the given RNG.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.state.draw_pile.shuffle(rng)
+    }
+
+    /// Puts the discard pile back into the draw pile and shuffles it.
+    pub fn reshuffle(&mut self) {
+        self.reshuffle_with(&mut rand::thread_rng())
+    }
+
+    pub fn reshuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let discard = std::mem::take(&mut self.state.discard);
+        self.state.draw_pile.extend(discard);
+        self.shuffle_with(rng)
+    }
+
+    /// Draws the top card into the hand.
+    pub fn draw(&mut self) -> DiroResult<Card> {
+        let i = self
+            .state
+            .draw_pile
+            .pop()
+            .ok_or_else(|| DiroError::DeckEmpty(self.name.clone()))?;
+        self.state.hand.push(i);
+        Ok(self.cards[i].clone())
+    }
+
+    /// The top `count` cards, from the top down, without drawing them.
+    pub fn peek(&self, count: usize) -> Vec<&Card> {
+        self.state
+            .draw_pile
+            .iter()
+            .rev()
+            .take(count)
+            .map(|i| &self.cards[*i])
+            .collect()
+    }
+
+    /// Moves a drawn card from the hand to the discard pile.
+    pub fn discard(&mut self, card: &Card) -> DiroResult<()> {
+        let position = self
+            .state
+            .hand
+            .iter()
+            .position(|i| self.cards[*i] == *card)
+            .ok_or_else(|| DiroError::CardNotInHand(card.name.clone()))?;
+        let i = self.state.hand.remove(position);
+        self.state.discard.push(i);
+        Ok(())
+    }
+
+    /// Discards every drawn card, e.g. at the end of a round.
+    pub fn discard_hand(&mut self) {
+        let hand = std::mem::take(&mut self.state.hand);
+        self.state.discard.extend(hand);
+    }
+}
+
+impl Decks {
+    pub fn insert(&mut self, deck: Deck) {
+        self.0.insert(deck.name.clone(), deck);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Deck> {
+        self.0.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Deck> {
+        self.0.get_mut(name)
+    }
+
+    /// Draws from the deck called `name`.
+    pub fn draw(&mut self, name: &str) -> DiroResult<Card> {
+        self.get_mut(name)
+            .ok_or_else(|| DiroError::UnknownDeck(name.to_string()))?
+            .draw()
+    }
+}
+
+#[test]
+fn deck_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut deck = Deck::standard("init");
+    assert_eq!(deck.remaining(), 54);
+    assert_eq!(deck.peek(2)[0].name, "2♣");
+    assert_eq!(Deck::tarot("t").cards().len(), 78);
+
+    let mut rng = StdRng::seed_from_u64(1);
+    deck.shuffle_with(&mut rng);
+    let top = deck.peek(3).into_iter().cloned().collect::<Vec<_>>();
+    let drawn = (0..3).map(|_| deck.draw().unwrap()).collect::<Vec<_>>();
+    assert_eq!(top, drawn);
+    assert_eq!(deck.remaining(), 51);
+
+    deck.discard(&drawn[0]).unwrap();
+    assert!(matches!(
+        deck.discard(&drawn[0]),
+        Err(DiroError::CardNotInHand(_))
+    ));
+    deck.reshuffle_with(&mut rng);
+    assert_eq!(deck.remaining(), 52);
+    assert_eq!(deck.state().hand.len(), 2);
+    deck.discard_hand();
+    assert_eq!(deck.state().discard.len(), 2);
+
+    let mut same = Deck::standard("init");
+    same.set_state(deck.state().clone()).unwrap();
+    assert_eq!(same, deck);
+    let mut broken = deck.state().clone();
+    broken.discard.push(broken.draw_pile[0]);
+    assert!(matches!(
+        same.set_state(broken),
+        Err(DiroError::InvalidDeckState(_))
+    ));
+
+    let mut small = Deck::new("small", vec![Card::new("only", 1)]);
+    small.draw().unwrap();
+    assert!(matches!(small.draw(), Err(DiroError::DeckEmpty(_))));
+}
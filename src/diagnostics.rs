@@ -0,0 +1,637 @@
+//! Fairness audit of the dice: rolls a `Dice` many times and compares the
+//! results with its exact distribution by chi-square and Kolmogorov–Smirnov
+//! tests.
+
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::error::{DiroError, DiroResult};
+use crate::fitd::FitdKind;
+use crate::gurps::GurpsOutcome;
+use crate::l5r::ten_dice_rule;
+use crate::narrative::{NarrativeDie, Symbols};
+use crate::parse::{parse, DiroAst};
+use crate::{Dice, RollResult};
+
+/// The probability of every result.
+pub type Distribution = BTreeMap<i32, f64>;
+
+/// Exploding chains are cut once their remaining probability drops below it.
+const EPSILON: f64 = 1e-12;
+
+/// The most combinations enumerated for a single distribution.
+const MAX_COMBINATIONS: u64 = 1 << 20;
+
+/// An expression of every `Dice` variant, checked by the regression tests.
+pub const PRESETS: [&str; 27] = [
+    "d100",
+    "2d100",
+    "d100b2",
+    "d100p",
+    "3d6",
+    "4d6k3",
+    "5d8q2",
+    "2d20adv",
+    "1d20dis",
+    "6a8",
+    "3c8",
+    "4dF",
+    "4dF.1",
+    "2g1y3p1k1b1r",
+    "3w",
+    "yz3s2g1",
+    "fitd3",
+    "fitd0",
+    "fitd2r",
+    "swd8+1",
+    "12k4",
+    "{d4,2d6,d8,d10}",
+    "gurps12",
+    "sr8e",
+    "v5 7h2",
+    "is2",
+    "isp7",
+];
+
+/// The results of the fairness tests of one dice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// The dice expression, e.g. `4D6K3`.
+    pub dice: String,
+    pub samples: usize,
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    pub chi_square_p: f64,
+    /// The largest gap between the empirical and exact CDF.
+    pub ks_statistic: f64,
+    /// Conservative, as the distributions are discrete.
+    pub ks_p: f64,
+    /// Samples the exact distribution gives no chance at all.
+    pub impossible: usize,
+}
+
+impl Report {
+    /// Whether no test rejects the dice at significance `alpha`.
+    pub fn passed(&self, alpha: f64) -> bool {
+        self.impossible == 0 && self.chi_square_p >= alpha && self.ks_p >= alpha
+    }
+
+    /// e.g. `4D6K3: 10000 samples, chi² 12.30 (df 14, p 0.583), KS 0.0071 (p 0.706)`.
+    pub fn describe(&self) -> String {
+        let mut s = format!(
+            "{}: {} samples, chi² {:.2} (df {}, p {:.3}), KS {:.4} (p {:.3})",
+            self.dice,
+            self.samples,
+            self.chi_square,
+            self.degrees_of_freedom,
+            self.chi_square_p,
+            self.ks_statistic,
+            self.ks_p
+        );
+        if self.impossible > 0 {
+            s.push_str(&format!(", {} impossible", self.impossible));
+        }
+        s
+    }
+}
+
+/// Rolls `dice` `samples` times and tests the results.
+pub fn audit(dice: &Dice, samples: usize) -> DiroResult<Report> {
+    audit_with(dice, samples, &mut rand::thread_rng())
+}
+
+/// Like `audit`, but rolls with the given RNG.
+pub fn audit_with<R: Rng + ?Sized>(dice: &Dice, samples: usize, rng: &mut R) -> DiroResult<Report> {
+    let results = (0..samples)
+        .map(|_| dice.roll_with(rng).result())
+        .collect::<Vec<_>>();
+    test(&dice.expr(), &distribution(dice)?, &results)
+}
+
+/// Audits every expression of `PRESETS`.
+pub fn audit_presets_with<R: Rng + ?Sized>(samples: usize, rng: &mut R) -> DiroResult<Vec<Report>> {
+    PRESETS
+        .iter()
+        .map(|source| match parse(source)? {
+            DiroAst::Dice(dice, _) => audit_with(&dice, samples, rng),
+            _ => Err(DiroError::NoDistribution(source.to_string())),
+        })
+        .collect()
+}
+
+/// Tests sampled `results` against the exact distribution.
+pub fn test(dice: &str, distribution: &Distribution, results: &[i32]) -> DiroResult<Report> {
+    if results.is_empty() {
+        return Err(DiroError::NoDistribution(dice.to_string()));
+    }
+    let samples = results.len();
+    let mut observed = BTreeMap::<i32, usize>::new();
+    for r in results {
+        *observed.entry(*r).or_default() += 1;
+    }
+    let impossible = observed
+        .iter()
+        .filter(|(r, _)| distribution.get(r).copied().unwrap_or(0.0) <= 0.0)
+        .map(|(_, count)| count)
+        .sum();
+
+    // adjacent results are binned until every bin expects at least 5
+    let mut bins: Vec<(f64, f64)> = vec![];
+    let mut bin = (0.0, 0.0);
+    for (r, p) in distribution {
+        bin.0 += p * samples as f64;
+        bin.1 += *observed.get(r).unwrap_or(&0) as f64;
+        if bin.0 >= 5.0 {
+            bins.push(std::mem::take(&mut bin));
+        }
+    }
+    match bins.last_mut() {
+        Some(last) => {
+            last.0 += bin.0;
+            last.1 += bin.1;
+        }
+        None => bins.push(bin),
+    }
+    let chi_square = bins.iter().map(|(e, o)| (o - e) * (o - e) / e).sum();
+    let degrees_of_freedom = bins.len() - 1;
+    let chi_square_p = if degrees_of_freedom == 0 {
+        1.0
+    } else {
+        gamma_q(degrees_of_freedom as f64 / 2.0, chi_square / 2.0)
+    };
+
+    let mut points = distribution
+        .keys()
+        .chain(observed.keys())
+        .collect::<Vec<_>>();
+    points.sort_unstable();
+    points.dedup();
+    let (mut cdf, mut ecdf, mut ks_statistic) = (0.0, 0, 0.0f64);
+    for r in points {
+        cdf += distribution.get(r).unwrap_or(&0.0);
+        ecdf += observed.get(r).unwrap_or(&0);
+        ks_statistic = ks_statistic.max((ecdf as f64 / samples as f64 - cdf).abs());
+    }
+    let n = (samples as f64).sqrt();
+
+    Ok(Report {
+        dice: dice.to_string(),
+        samples,
+        chi_square,
+        degrees_of_freedom,
+        chi_square_p,
+        ks_statistic,
+        ks_p: kolmogorov_q((n + 0.12 + 0.11 / n) * ks_statistic),
+        impossible,
+    })
+}
+
+/// The exact distribution of `Dice::roll(..).result()`, exploding dice are
+/// cut off once their tail is negligible.
+pub fn distribution(dice: &Dice) -> DiroResult<Distribution> {
+    let unsupported = || DiroError::NoDistribution(dice.expr());
+    let d = match dice {
+        Dice::D100 { count, bp } => {
+            let faces = vec![10; bp.unsigned_abs() as usize + 2];
+            let single = enumerate(&faces, |v| {
+                let digits = v.iter().map(|d| *d as u8 - 1).collect::<Vec<_>>();
+                RollResult::D100(vec![(
+                    [digits[0], digits[1]],
+                    *bp > 0,
+                    digits[2..].to_vec(),
+                )])
+                .result()
+            })
+            .ok_or_else(unsupported)?;
+            sum(&single, *count)
+        }
+        Dice::Dice { count, face, kq } => match kq {
+            0 => sum(&uniform(1, *face as i32), *count),
+            _ => keep(
+                &uniform(1, *face as i32),
+                *count,
+                kq.unsigned_abs(),
+                *kq > 0,
+            ),
+        },
+        Dice::Advantage {
+            count,
+            face,
+            advantage,
+        } => {
+            let rolled = count.checked_mul(2).ok_or_else(unsupported)?;
+            keep(&uniform(1, *face as i32), rolled, *count, *advantage)
+        }
+        Dice::ADice {
+            count,
+            face,
+            add_line,
+            success_line,
+        } => {
+            let chain = chain(*face, *add_line, |x| (x >= *success_line as i32) as i32)
+                .ok_or_else(unsupported)?;
+            sum(&chain, *count)
+        }
+        Dice::CDice {
+            count,
+            face,
+            count_line,
+        } => counted(*count, *face, *count_line).ok_or_else(unsupported)?,
+        Dice::FDice(count) => sum(&uniform(-1, 1), *count),
+        Dice::FDice1(count) => sum(
+            &BTreeMap::from([(-1, 1.0 / 6.0), (0, 4.0 / 6.0), (1, 1.0 / 6.0)]),
+            *count,
+        ),
+        Dice::Narrative(pool) => {
+            let force = pool.iter().all(|(_, die)| *die == NarrativeDie::Force);
+            pool.iter()
+                .fold(BTreeMap::from([(0, 1.0)]), |d, (count, die)| {
+                    let faces = die.faces();
+                    let mut single = Distribution::new();
+                    for face in faces {
+                        let s = Symbols::from_face(face);
+                        let value = if force {
+                            s.light - s.dark
+                        } else {
+                            s.net_success()
+                        };
+                        *single.entry(value).or_default() += 1.0 / faces.len() as f64;
+                    }
+                    convolve(&d, &sum(&single, *count))
+                })
+        }
+        Dice::YearZero { base, skill, gear } => {
+            let six = BTreeMap::from([(0, 5.0 / 6.0), (1, 1.0 / 6.0)]);
+            sum(&six, base.saturating_add(*skill).saturating_add(*gear))
+        }
+        Dice::Fitd { count, kind } => {
+            let (n, highest) = match count {
+                0 => (2, false),
+                _ => (*count, true),
+            };
+            let kept = keep(&uniform(1, 6), n, 1, highest);
+            match kind {
+                FitdKind::Resistance => {
+                    // two or more sixes are a critical, clearing 1 stress
+                    let n = n as i32;
+                    let one_six = n as f64 / 6.0 * (5.0f64 / 6.0).powi(n - 1);
+                    let critical = if highest { kept[&6] - one_six } else { 0.0 };
+                    let mut d = map(&kept, |v| 6 - v);
+                    *d.get_mut(&0).unwrap() -= critical;
+                    *d.entry(-1).or_default() += critical;
+                    d
+                }
+                _ => kept,
+            }
+        }
+        Dice::Savage { face, modifier } => {
+            let traits = chain(*face, *face, |x| x).ok_or_else(unsupported)?;
+            let wild = chain(6, 6, |x| x).ok_or_else(unsupported)?;
+            map(&highest(&traits, &wild), |v| v + modifier)
+        }
+        Dice::RollKeep { rolled, kept } => {
+            let (rolled, kept, bonus) = ten_dice_rule(*rolled, *kept);
+            let chain = chain(10, 10, |x| x).ok_or_else(unsupported)?;
+            map(&keep(&chain, rolled, kept, true), |v| v + bonus)
+        }
+        Dice::Cortex(pool) => {
+            let faces = pool
+                .iter()
+                .flat_map(|(count, face)| (0..*count).map(move |_| *face))
+                .collect::<Vec<_>>();
+            enumerate(&faces, |v| {
+                RollResult::Cortex(faces.iter().copied().zip(v.iter().copied()).collect()).result()
+            })
+            .ok_or_else(unsupported)?
+        }
+        Dice::Gurps(skill) => map(&sum(&uniform(1, 6), 3), |v| {
+            GurpsOutcome::new(v, *skill as i32).margin(v, *skill as i32)
+        }),
+        Dice::Shadowrun { count, edge } => {
+            let line = if *edge { 6 } else { 7 };
+            let chain = chain(6, line, |x| (x >= 5) as i32).ok_or_else(unsupported)?;
+            sum(&chain, *count)
+        }
+        Dice::V5 { pool, .. } => v5(*pool),
+        Dice::Ironsworn { score, progress } => {
+            let faces = if *progress {
+                vec![10, 10]
+            } else {
+                vec![6, 10, 10]
+            };
+            enumerate(&faces, |v| {
+                let (action, challenge) = match v {
+                    [a, c0, c1] => (Some(*a), [*c0, *c1]),
+                    [c0, c1] => (None, [*c0, *c1]),
+                    _ => unreachable!(),
+                };
+                RollResult::Ironsworn {
+                    action,
+                    score: *score,
+                    challenge,
+                    momentum: None,
+                }
+                .result()
+            })
+            .ok_or_else(unsupported)?
+        }
+    };
+    Ok(d)
+}
+
+fn uniform(low: i32, high: i32) -> Distribution {
+    let p = 1.0 / (high - low + 1) as f64;
+    (low..=high).map(|v| (v, p)).collect()
+}
+
+fn map(d: &Distribution, f: impl Fn(i32) -> i32) -> Distribution {
+    let mut mapped = Distribution::new();
+    for (v, p) in d {
+        *mapped.entry(f(*v)).or_default() += p;
+    }
+    mapped
+}
+
+fn convolve(a: &Distribution, b: &Distribution) -> Distribution {
+    let mut d = Distribution::new();
+    for (x, p) in a {
+        for (y, q) in b {
+            *d.entry(x + y).or_default() += p * q;
+        }
+    }
+    d
+}
+
+/// The sum of `count` independent results.
+fn sum(d: &Distribution, count: u8) -> Distribution {
+    (0..count).fold(BTreeMap::from([(0, 1.0)]), |acc, _| convolve(&acc, d))
+}
+
+/// The higher of two independent results.
+fn highest(a: &Distribution, b: &Distribution) -> Distribution {
+    let mut d = Distribution::new();
+    for (x, p) in a {
+        for (y, q) in b {
+            *d.entry(*x.max(y)).or_default() += p * q;
+        }
+    }
+    d
+}
+
+/// The sum of the highest (or lowest) `kept` of `count` independent results.
+fn keep(d: &Distribution, count: u8, kept: u8, highest: bool) -> Distribution {
+    let mut values = d.iter().map(|(v, p)| (*v, *p)).collect::<Vec<_>>();
+    if highest {
+        values.reverse();
+    }
+    // (dice placed so far, kept sum) while placing values best first
+    let mut states = BTreeMap::from([((0u8, 0i32), 1.0)]);
+    let mut rest = 1.0;
+    for (i, (v, p)) in values.iter().enumerate() {
+        let q = if i + 1 == values.len() {
+            1.0
+        } else {
+            (p / rest).min(1.0)
+        };
+        let mut next = BTreeMap::new();
+        for ((placed, total), prob) in states {
+            let left = count - placed;
+            for m in 0..=left {
+                let pm = binomial(left, m) * q.powi(m as i32) * (1.0 - q).powi((left - m) as i32);
+                if prob * pm < EPSILON {
+                    continue;
+                }
+                let taken = m.min(kept.saturating_sub(placed)) as i32;
+                *next.entry((placed + m, total + taken * v)).or_default() += prob * pm;
+            }
+        }
+        states = next;
+        rest -= p;
+    }
+    let mut kept = Distribution::new();
+    for ((_, total), p) in states {
+        *kept.entry(total).or_default() += p;
+    }
+    kept
+}
+
+fn binomial(n: u8, k: u8) -> f64 {
+    (0..k).fold(1.0, |c, i| c * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// The sum of `value` over a die rolled again at or above `line`.
+fn chain(face: u16, line: u16, value: impl Fn(i32) -> i32) -> Option<Distribution> {
+    if face == 0 || line <= 1 {
+        return None;
+    }
+    let mut d = Distribution::new();
+    let mut pending = BTreeMap::from([(0, 1.0)]);
+    while !pending.is_empty() {
+        let mut next = BTreeMap::new();
+        for (total, p) in pending {
+            let p = p / face as f64;
+            for x in 1..=face as i32 {
+                let target = if x >= line as i32 { &mut next } else { &mut d };
+                *target.entry(total + value(x)).or_default() += p;
+            }
+        }
+        next.retain(|_, p| *p >= EPSILON);
+        pending = next;
+    }
+    Some(d)
+}
+
+/// `RollResult::CDice`, the dice at or above `line` add up and roll again
+/// in the next round, then the highest die of the last round is added.
+fn counted(count: u8, face: u16, line: u16) -> Option<Distribution> {
+    if face == 0 || line <= 1 {
+        return None;
+    }
+    let line = line as i32;
+    let f = face as f64;
+    let mut d = Distribution::new();
+    // (dice rolled this round, total so far)
+    let mut pending = BTreeMap::from([((count, 0), 1.0)]);
+    while !pending.is_empty() {
+        let mut next = BTreeMap::new();
+        for ((dice, total), p) in pending {
+            // a round without any die at or above the line is the last
+            for v in 1..line.min(face as i32 + 1) {
+                let high =
+                    (v as f64 / f).powi(dice as i32) - ((v - 1) as f64 / f).powi(dice as i32);
+                *d.entry(total + v).or_default() += p * high;
+            }
+            // (dice at or above the line, their sum)
+            let mut rounds = BTreeMap::from([((0u8, 0), 1.0)]);
+            for _ in 0..dice {
+                let mut r = BTreeMap::new();
+                for ((m, s), q) in rounds {
+                    *r.entry((m, s)).or_default() += q * (line - 1).min(face as i32) as f64 / f;
+                    for x in line..=face as i32 {
+                        *r.entry((m + 1, s + x)).or_default() += q / f;
+                    }
+                }
+                rounds = r;
+            }
+            for ((m, s), q) in rounds {
+                if m > 0 && p * q >= EPSILON {
+                    *next.entry((m, total + s)).or_default() += p * q;
+                }
+            }
+        }
+        pending = next;
+    }
+    Some(d)
+}
+
+/// Successes of `pool` d10s, each pair of 10s adds 2 more.
+fn v5(pool: u8) -> Distribution {
+    let mut d = Distribution::new();
+    for tens in 0..=pool {
+        for high in 0..=pool - tens {
+            let low = pool - tens - high;
+            let p = binomial(pool, tens)
+                * binomial(pool - tens, high)
+                * 0.1f64.powi(tens as i32)
+                * 0.4f64.powi(high as i32)
+                * 0.5f64.powi(low as i32);
+            let successes = (tens + high) as i32 + tens as i32 / 2 * 2;
+            *d.entry(successes).or_default() += p;
+        }
+    }
+    d
+}
+
+/// Every combination of the given dice, one face each.
+fn enumerate(faces: &[u16], f: impl Fn(&[i32]) -> i32) -> Option<Distribution> {
+    let combinations = faces
+        .iter()
+        .try_fold(1u64, |n, face| n.checked_mul(*face as u64))?;
+    if combinations == 0 || combinations > MAX_COMBINATIONS {
+        return None;
+    }
+    let p = 1.0 / combinations as f64;
+    let mut d = Distribution::new();
+    let mut values = vec![1; faces.len()];
+    for _ in 0..combinations {
+        *d.entry(f(&values)).or_default() += p;
+        for (v, face) in values.iter_mut().zip(faces) {
+            if *v < *face as i32 {
+                *v += 1;
+                break;
+            }
+            *v = 1;
+        }
+    }
+    Some(d)
+}
+
+/// The regularized upper incomplete gamma function Q(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let ln_front = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        // series of P(a, x)
+        let (mut term, mut total, mut n) = (1.0 / a, 1.0 / a, a);
+        while term.abs() > total.abs() * 1e-15 {
+            n += 1.0;
+            term *= x / n;
+            total += term;
+        }
+        1.0 - total * ln_front.exp()
+    } else {
+        // continued fraction of Q(a, x) by Lentz's method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        ln_front.exp() * h
+    }
+}
+
+/// Lanczos approximation of ln Γ(x).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |s, (i, c)| s + c / (x + 1.0 + i as f64));
+    (2.5066282746310005 * series / x).ln() - tmp + (x + 0.5) * tmp.ln()
+}
+
+/// The chance the Kolmogorov statistic exceeds `lambda`.
+fn kolmogorov_q(lambda: f64) -> f64 {
+    if lambda < 0.042 {
+        1.0
+    } else if lambda < 1.18 {
+        let y = (-1.233_700_550_136_17 / (lambda * lambda)).exp();
+        let p =
+            2.256_758_334_191_025 * (-y.ln()).sqrt() * (y + y.powi(9) + y.powi(25) + y.powi(49));
+        (1.0 - p).clamp(0.0, 1.0)
+    } else {
+        let x = (-2.0 * lambda * lambda).exp();
+        (2.0 * (x - x.powi(4) + x.powi(9))).clamp(0.0, 1.0)
+    }
+}
+
+#[test]
+fn diagnostics_test() {
+    // chi-square of 3.84 with one degree of freedom is the 5% critical value
+    assert!((gamma_q(0.5, 3.841 / 2.0) - 0.05).abs() < 1e-3);
+    assert!((kolmogorov_q(1.358) - 0.05).abs() < 1e-3);
+
+    let d = distribution(&Dice::dice(2, 6, 0).unwrap()).unwrap();
+    assert!((d[&7] - 6.0 / 36.0).abs() < 1e-12);
+    let d = distribution(&Dice::dice(4, 6, 3).unwrap()).unwrap();
+    assert!((d[&18] - 21.0 / 1296.0).abs() < 1e-12);
+    for source in PRESETS {
+        let DiroAst::Dice(dice, _) = parse(source).unwrap() else {
+            panic!("{} is not a dice", source);
+        };
+        let total = distribution(&dice).unwrap().values().sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-6, "{}: {}", source, total);
+    }
+
+    // the d100 used to roll its digits from 0-8, missing 9 and 90-99
+    let d100 = distribution(&Dice::d100(1, 0).unwrap()).unwrap();
+    let biased = (0..8100)
+        .map(|i| i / 9 % 9 * 10 + i % 9 + 1)
+        .collect::<Vec<_>>();
+    let report = test("D100", &d100, &biased).unwrap();
+    assert!(!report.passed(0.001));
+    let fair = (0..8100).map(|i| i % 100 + 1).collect::<Vec<_>>();
+    assert!(test("D100", &d100, &fair).unwrap().passed(0.001));
+
+    let report = test("1D6", &uniform(1, 6), &[1, 2, 7]).unwrap();
+    assert_eq!(report.impossible, 1);
+}
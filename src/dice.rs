@@ -1,9 +1,21 @@
 use rand::Rng;
 use std::fmt::Debug;
 
+use crate::cortex::CortexSelection;
 use crate::error::{DiroError, DiroResult};
+use crate::fitd::{resistance_stress, FitdKind, FitdOutcome};
+use crate::gurps::GurpsOutcome;
+use crate::ironsworn::{IronswornOutcome, MAX_SCORE};
+use crate::l5r::ten_dice_rule;
+use crate::narrative::{NarrativeDie, Symbols};
+use crate::render::{Mark, Plain, Renderer};
+use crate::savage::SavageOutcome;
+use crate::shadowrun::{is_hit, ShadowrunOutcome, ShadowrunRoll};
+use crate::v5::V5Outcome;
+use crate::yearzero::YearZeroRoll;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dice {
     D100 {
         count: u8,
@@ -26,9 +38,65 @@ pub enum Dice {
         count_line: u16,
     },
     FDice(u8),
+    /// `dF.1`, a d6 with one plus, one minus and four blanks.
+    FDice1(u8),
+    /// `adv`/`dis`, rolls twice the dice and keeps the highest/lowest `count`.
+    Advantage {
+        count: u8,
+        face: u16,
+        advantage: bool,
+    },
+    /// A Genesys / Star Wars pool such as `2g1y3p1k`.
+    Narrative(Vec<(u8, NarrativeDie)>),
+    /// A Year Zero pool such as `yz3s2g1`.
+    YearZero {
+        base: u8,
+        skill: u8,
+        gear: u8,
+    },
+    /// `fitdN`, keeps the highest of N d6, or the lowest of 2 for zero dice.
+    Fitd {
+        count: u8,
+        kind: FitdKind,
+    },
+    /// `swd8+1`, a trait die and a d6 wild die, both acing.
+    Savage {
+        face: u16,
+        modifier: i32,
+    },
+    /// `7k3`, rolls exploding d10s and keeps the highest, as written
+    /// before the Ten Dice Rule.
+    RollKeep {
+        rolled: u8,
+        kept: u8,
+    },
+    /// A Cortex pool of mixed dice such as `{d8,2d6,d10}`.
+    Cortex(Vec<(u8, u16)>),
+    /// `gurps12`, a 3d6 roll-under check against the effective skill.
+    Gurps(u8),
+    /// `sr12`, counts hits on 5 and 6, `sr12e` pushes the limit and
+    /// explodes sixes.
+    Shadowrun {
+        count: u8,
+        edge: bool,
+    },
+    /// `v5 7h2 diff3`, `hunger` of the `pool` d10s are hunger dice. Without
+    /// a difficulty any success is a win.
+    V5 {
+        pool: u8,
+        hunger: u8,
+        difficulty: Option<u8>,
+    },
+    /// `is2` rolls a d6 action die plus 2, `isp7` is a progress roll
+    /// scoring 7, both against two d10 challenge dice.
+    Ironsworn {
+        score: u8,
+        progress: bool,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RollResult {
     D100(Vec<([u8; 2], bool, Vec<u8>)>),
     Dice {
@@ -45,81 +113,600 @@ pub enum RollResult {
         count_line: u16,
     },
     FDice(Vec<i8>),
+    Narrative(Vec<(NarrativeDie, Symbols)>),
+    /// The original roll followed by every push.
+    YearZero(Vec<YearZeroRoll>),
+    /// `roll` is the underlying `RollResult::Dice` keeping one die.
+    Fitd {
+        kind: FitdKind,
+        roll: Box<RollResult>,
+    },
+    /// The acing chains of the trait and the wild die.
+    Savage {
+        face: u16,
+        traits: Vec<i32>,
+        wild: Vec<i32>,
+        modifier: i32,
+    },
+    /// The explosion chain of every rolled d10.
+    RollKeep {
+        chains: Vec<Vec<i32>>,
+        kept: u8,
+        bonus: i32,
+        /// `(rolled, kept)` as written, before the Ten Dice Rule.
+        written: (u8, u8),
+    },
+    /// `(face, value)` of every die in the pool.
+    Cortex(Vec<(u16, i32)>),
+    /// `roll` is the underlying 3d6 `RollResult::Dice`.
+    Gurps {
+        skill: u8,
+        roll: Box<RollResult>,
+    },
+    /// The original roll followed by the Second Chance reroll.
+    Shadowrun(Vec<ShadowrunRoll>),
+    V5 {
+        regular: Vec<i32>,
+        hunger: Vec<i32>,
+        difficulty: Option<u8>,
+    },
+    /// `action` is `None` on a progress roll, `momentum` is set once burned.
+    Ironsworn {
+        action: Option<i32>,
+        score: u8,
+        challenge: [i32; 2],
+        momentum: Option<i32>,
+    },
 }
 
 impl RollResult {
     pub fn detail(&self) -> String {
+        self.render(&Plain)
+    }
+
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> String {
         match self {
             RollResult::D100(v) => v
                 .iter()
                 .map(|r| {
+                    let chosen = d100_chosen(r);
                     let mut s = String::new();
-                    s.push_str(r.0[0].to_string().as_str());
-                    s.push_str(r.0[1].to_string().as_str());
-                    for i in r.2.iter() {
-                        if r.1 {
-                            s.push_str("B");
+                    s.push_str(&renderer.die(
+                        &r.0[0].to_string(),
+                        if chosen.is_some() {
+                            Mark::Dropped
                         } else {
-                            s.push_str("P");
-                        }
-                        s.push_str(i.to_string().as_str());
+                            Mark::Normal
+                        },
+                    ));
+                    s.push_str(&renderer.die(&r.0[1].to_string(), Mark::Normal));
+                    for (index, i) in r.2.iter().enumerate() {
+                        s.push_str(&renderer.text(if r.1 { "B" } else { "P" }));
+                        s.push_str(&renderer.die(
+                            &i.to_string(),
+                            if chosen == Some(index) {
+                                Mark::Normal
+                            } else {
+                                Mark::Dropped
+                            },
+                        ));
                     }
                     s
                 })
                 .collect::<Vec<_>>()
-                .join("+"),
-            RollResult::Dice { result, .. } => result
-                .iter()
-                .map(|r| r.to_string())
-                .collect::<Vec<_>>()
-                .join("+"),
-            RollResult::ADice { result, .. } => result
+                .join(&renderer.text("+")),
+            RollResult::Dice { kq, result } => {
+                let kept = kept(*kq, result);
+                result
+                    .iter()
+                    .zip(kept)
+                    .map(|(r, k)| {
+                        renderer.die(&r.to_string(), if k { Mark::Normal } else { Mark::Dropped })
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text("+"))
+            }
+            RollResult::ADice {
+                result,
+                success_line,
+                ..
+            } => render_rounds(renderer, result, |x| {
+                if x >= *success_line as i32 {
+                    Mark::Success
+                } else {
+                    Mark::Normal
+                }
+            }),
+            RollResult::CDice { result, count_line } => render_rounds(renderer, result, |x| {
+                if x >= *count_line as i32 {
+                    Mark::Success
+                } else {
+                    Mark::Normal
+                }
+            }),
+            RollResult::FDice(f) => f
                 .iter()
-                .enumerate()
-                .map(|(round, r)| {
-                    let mut s = String::new();
-                    s.push_str(format!("[{}]:", round + 1).as_str());
-                    s.push_str(
-                        r.iter()
-                            .map(|x| x.to_string())
-                            .collect::<Vec<_>>()
-                            .join(" ")
-                            .as_str(),
-                    );
-                    s
+                .map(|x| {
+                    if x > &0 {
+                        renderer.die("+", Mark::Success)
+                    } else if x == &0 {
+                        renderer.die("0", Mark::Normal)
+                    } else {
+                        renderer.die("-", Mark::Failure)
+                    }
                 })
                 .collect::<Vec<_>>()
-                .join(" "),
-            RollResult::CDice { result, .. } => result
+                .join(""),
+            RollResult::Narrative(faces) => {
+                let dice = faces
+                    .iter()
+                    .map(|(die, face)| {
+                        let mark = if face.triumph > 0 {
+                            Mark::Critical
+                        } else if face.net_success() > 0 || face.net_advantage() > 0 {
+                            Mark::Success
+                        } else if face.net_success() < 0 || face.net_advantage() < 0 {
+                            Mark::Failure
+                        } else {
+                            Mark::Normal
+                        };
+                        format!(
+                            "{}{}",
+                            renderer.text(&format!("{}:", die.letter())),
+                            renderer.die(&face.face(), mark)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text(" "));
+                format!(
+                    "{}{}",
+                    dice,
+                    renderer.text(&format!(" ({})", self.symbols().unwrap_or_default()))
+                )
+            }
+            RollResult::YearZero(rolls) => rolls
                 .iter()
                 .enumerate()
                 .map(|(round, r)| {
-                    let mut s = String::new();
-                    s.push_str(format!("[{}]:", round + 1).as_str());
+                    let mut s = renderer.text(&format!("[{}]:", round + 1));
+                    let groups = [
+                        ("B", &r.base, true),
+                        ("S", &r.skill, false),
+                        ("G", &r.gear, true),
+                    ];
                     s.push_str(
-                        r.iter()
-                            .map(|x| x.to_string())
+                        &groups
+                            .iter()
+                            .filter(|(_, dice, _)| !dice.is_empty())
+                            .map(|(name, dice, ones)| {
+                                format!(
+                                    "{}{}",
+                                    renderer.text(&format!("{}:", name)),
+                                    dice.iter()
+                                        .map(|d| {
+                                            let mark = if *d == 6 {
+                                                Mark::Success
+                                            } else if *ones && *d == 1 {
+                                                Mark::Failure
+                                            } else {
+                                                Mark::Normal
+                                            };
+                                            renderer.die(&d.to_string(), mark)
+                                        })
+                                        .collect::<Vec<_>>()
+                                        .join(&renderer.text(","))
+                                )
+                            })
                             .collect::<Vec<_>>()
-                            .join(" ")
-                            .as_str(),
+                            .join(&renderer.text(" ")),
                     );
                     s
                 })
                 .collect::<Vec<_>>()
-                .join(" "),
-            RollResult::FDice(f) => f
-                .iter()
-                .map(|x| {
-                    if x > &0 {
-                        "+"
-                    } else if x == &0 {
-                        "0"
-                    } else {
-                        "-"
+                .join(&renderer.text(" ")),
+            RollResult::Fitd { kind, roll } => {
+                let outcome = self.fitd_outcome().unwrap_or(FitdOutcome::Failure);
+                let mut s = roll.render(renderer);
+                s.push_str(&renderer.text(&format!(" ({}", outcome.describe(*kind))));
+                if *kind == FitdKind::Resistance {
+                    s.push_str(&renderer.text(&format!(", stress {}", self.result())));
+                }
+                s.push_str(&renderer.text(")"));
+                s
+            }
+            RollResult::Savage {
+                face,
+                traits,
+                wild,
+                modifier,
+            } => {
+                let outcome = self.savage_outcome().unwrap_or(SavageOutcome::Failure);
+                let wild_kept = wild.iter().sum::<i32>() > traits.iter().sum::<i32>();
+                let chain = |name: &str, dice: &[i32], face: u16, kept: bool| {
+                    let dice = dice
+                        .iter()
+                        .enumerate()
+                        .map(|(i, d)| {
+                            let mark = if outcome == SavageOutcome::CriticalFailure && i == 0 {
+                                Mark::Failure
+                            } else if !kept {
+                                Mark::Dropped
+                            } else if *d == face as i32 {
+                                Mark::Success
+                            } else {
+                                Mark::Normal
+                            };
+                            renderer.die(&d.to_string(), mark)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&renderer.text("+"));
+                    format!("{}{}", renderer.text(&format!("{}:", name)), dice)
+                };
+                let mut s = chain("T", traits, *face, !wild_kept);
+                s.push_str(&renderer.text(" "));
+                s.push_str(&chain("W", wild, 6, wild_kept));
+                if *modifier != 0 {
+                    s.push_str(&renderer.text(&format!(" {:+}", modifier)));
+                }
+                s.push_str(&renderer.text(&format!(" ({})", outcome.describe())));
+                s
+            }
+            RollResult::RollKeep {
+                chains,
+                kept: keep,
+                bonus,
+                written,
+            } => {
+                let totals = chains.iter().map(|c| c.iter().sum()).collect::<Vec<_>>();
+                let mut s = chains
+                    .iter()
+                    .zip(kept(*keep as i8, &totals))
+                    .map(|(chain, k)| {
+                        let dice = chain
+                            .iter()
+                            .map(|d| {
+                                let mark = if k { Mark::Normal } else { Mark::Dropped };
+                                renderer.die(&d.to_string(), mark)
+                            })
+                            .collect::<Vec<_>>()
+                            .join(&renderer.text("+"));
+                        if chain.len() > 1 {
+                            renderer.group(&dice)
+                        } else {
+                            dice
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text(" "));
+                if *bonus != 0 {
+                    s.push_str(&renderer.text(&format!(" {:+}", bonus)));
+                }
+                if *written != (chains.len() as u8, *keep) {
+                    let mut pool = format!("{}K{}", chains.len(), keep);
+                    if *bonus != 0 {
+                        pool.push_str(&format!("{:+}", bonus));
                     }
-                })
-                .collect::<Vec<_>>()
-                .join(""),
+                    s.push_str(&renderer.text(&format!(" (→ {})", pool)));
+                }
+                s
+            }
+            RollResult::Cortex(pool) => {
+                let selection = CortexSelection::best(pool, 2);
+                let mut s = pool
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (face, value))| {
+                        let mark = if selection.hitches.contains(&i) {
+                            Mark::Failure
+                        } else if selection.effect == Some(i) {
+                            Mark::Critical
+                        } else if selection.total.contains(&i) {
+                            Mark::Normal
+                        } else {
+                            Mark::Dropped
+                        };
+                        format!(
+                            "{}{}",
+                            renderer.text(&format!("d{}:", face)),
+                            renderer.die(&value.to_string(), mark)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text(" "));
+                s.push_str(&renderer.text(&format!(" (effect d{}", selection.effect_die(pool))));
+                match selection.hitches.len() {
+                    0 => {}
+                    _ if selection.botch(pool) => s.push_str(&renderer.text(", botch")),
+                    1 => s.push_str(&renderer.text(", 1 hitch")),
+                    n => s.push_str(&renderer.text(&format!(", {} hitches", n))),
+                }
+                s.push_str(&renderer.text(")"));
+                s
+            }
+            RollResult::Gurps { skill, roll } => {
+                let outcome = self.gurps_outcome().unwrap_or(GurpsOutcome::Failure);
+                format!(
+                    "{}{}",
+                    roll.render(renderer),
+                    renderer.text(&format!(
+                        " ({} vs {}, {}, margin {:+})",
+                        roll.result(),
+                        skill,
+                        outcome.describe(),
+                        self.result()
+                    ))
+                )
+            }
+            RollResult::Shadowrun(rolls) => {
+                let mut s = rolls
+                    .iter()
+                    .map(|roll| {
+                        render_rounds(renderer, &roll.rounds, |x| {
+                            if is_hit(x) {
+                                Mark::Success
+                            } else if x == 1 {
+                                Mark::Failure
+                            } else {
+                                Mark::Normal
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text(" / "));
+                let outcome = self.shadowrun_outcome().unwrap_or_default();
+                s.push_str(&renderer.text(&format!(" ({})", outcome.describe())));
+                s
+            }
+            RollResult::V5 {
+                regular,
+                hunger,
+                difficulty,
+            } => {
+                let groups = [("R", regular, false), ("H", hunger, true)];
+                let mut s = groups
+                    .iter()
+                    .filter(|(_, dice, _)| !dice.is_empty())
+                    .map(|(name, dice, hunger)| {
+                        format!(
+                            "{}{}",
+                            renderer.text(&format!("{}:", name)),
+                            dice.iter()
+                                .map(|d| {
+                                    let mark = match d {
+                                        10 => Mark::Critical,
+                                        6.. => Mark::Success,
+                                        1 if *hunger => Mark::Failure,
+                                        _ => Mark::Normal,
+                                    };
+                                    renderer.die(&d.to_string(), mark)
+                                })
+                                .collect::<Vec<_>>()
+                                .join(&renderer.text(","))
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text(" "));
+                let outcome = self.v5_outcome().unwrap_or_default();
+                s.push_str(&renderer.text(&format!(" ({})", outcome.describe(*difficulty))));
+                s
+            }
+            RollResult::Ironsworn {
+                action,
+                score,
+                challenge,
+                momentum,
+            } => {
+                let mut s = match action {
+                    Some(action) => {
+                        let mark = if momentum.is_some() {
+                            Mark::Dropped
+                        } else {
+                            Mark::Normal
+                        };
+                        let mut s = renderer.die(&action.to_string(), mark);
+                        if *score > 0 {
+                            s.push_str(&renderer.text(&format!("+{}", score)));
+                        }
+                        s
+                    }
+                    None => renderer.text(&format!("P:{}", score)),
+                };
+                if let Some(momentum) = momentum {
+                    s.push_str(&renderer.text(&format!(" M:{}", momentum)));
+                }
+                let total = self.ironsworn_score().unwrap_or_default();
+                s.push_str(&renderer.text(" vs "));
+                s.push_str(
+                    &challenge
+                        .iter()
+                        .map(|c| {
+                            let mark = if total > *c {
+                                Mark::Success
+                            } else {
+                                Mark::Failure
+                            };
+                            renderer.die(&c.to_string(), mark)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&renderer.text(",")),
+                );
+                if let Some(outcome) = self.ironsworn_outcome() {
+                    s.push_str(&renderer.text(&format!(" ({})", outcome.describe())));
+                }
+                s
+            }
+        }
+    }
+
+    /// The score compared with the challenge dice of an Ironsworn roll.
+    pub fn ironsworn_score(&self) -> Option<i32> {
+        match self {
+            Self::Ironsworn {
+                momentum: Some(momentum),
+                ..
+            } => Some((*momentum).min(MAX_SCORE)),
+            Self::Ironsworn { action, score, .. } => {
+                Some((action.unwrap_or(0) + *score as i32).min(MAX_SCORE))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn ironsworn_outcome(&self) -> Option<IronswornOutcome> {
+        match self {
+            Self::Ironsworn { challenge, .. } => {
+                Some(IronswornOutcome::new(self.ironsworn_score()?, *challenge))
+            }
+            _ => None,
+        }
+    }
+
+    /// Burns momentum on an Ironsworn action roll, replacing the action
+    /// score with `momentum`, which is capped at `MAX_SCORE`.
+    pub fn burn_momentum(&mut self, momentum: i32) -> DiroResult<()> {
+        match self {
+            Self::Ironsworn {
+                action: Some(_), ..
+            } if momentum <= 0 => Err(DiroError::NoMomentum(momentum)),
+            Self::Ironsworn {
+                action: Some(_),
+                momentum: burned,
+                ..
+            } => {
+                *burned = Some(momentum.min(MAX_SCORE));
+                Ok(())
+            }
+            _ => Err(DiroError::NotBurnable),
+        }
+    }
+
+    /// The successes and flags of a V5 pool.
+    pub fn v5_outcome(&self) -> Option<V5Outcome> {
+        match self {
+            Self::V5 {
+                regular,
+                hunger,
+                difficulty,
+            } => Some(V5Outcome::new(
+                regular,
+                hunger,
+                difficulty.unwrap_or(1) as i32,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The hits and glitches of a Shadowrun pool, after any Second Chance.
+    pub fn shadowrun_outcome(&self) -> Option<ShadowrunOutcome> {
+        match self {
+            Self::Shadowrun(rolls) => Some(rolls[rolls.len() - 1].outcome()),
+            _ => None,
+        }
+    }
+
+    /// Spends Edge on a Shadowrun roll to reroll every die that is not a hit,
+    /// unless the roll already pushed the limit.
+    pub fn second_chance(&mut self) -> DiroResult<()> {
+        self.second_chance_with(&mut rand::thread_rng())
+    }
+
+    pub fn second_chance_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> DiroResult<()> {
+        self.check_second_chance()?;
+        if let Self::Shadowrun(rolls) = self {
+            let rerolled = rolls[0].second_chance(rng);
+            rolls.push(rerolled);
+        }
+        Ok(())
+    }
+
+    /// Fails with `NoSecondChance` unless `second_chance` can reroll this.
+    pub(crate) fn check_second_chance(&self) -> DiroResult<()> {
+        match self {
+            // Push the Limit and Second Chance both spend Edge
+            Self::Shadowrun(rolls) if rolls.len() == 1 && !rolls[0].edge => Ok(()),
+            _ => Err(DiroError::NoSecondChance),
+        }
+    }
+
+    /// The outcome of a GURPS success roll.
+    pub fn gurps_outcome(&self) -> Option<GurpsOutcome> {
+        match self {
+            Self::Gurps { skill, roll } => Some(GurpsOutcome::new(roll.result(), *skill as i32)),
+            _ => None,
+        }
+    }
+
+    /// The best total and effect die of a Cortex pool.
+    pub fn cortex_selection(&self) -> Option<CortexSelection> {
+        match self {
+            Self::Cortex(pool) => Some(CortexSelection::best(pool, 2)),
+            _ => None,
+        }
+    }
+
+    /// The outcome of a Savage Worlds trait roll.
+    pub fn savage_outcome(&self) -> Option<SavageOutcome> {
+        match self {
+            Self::Savage { traits, wild, .. } => Some(SavageOutcome::new(
+                self.result(),
+                traits[0] == 1 && wild[0] == 1,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The outcome of a Forged in the Dark roll.
+    pub fn fitd_outcome(&self) -> Option<FitdOutcome> {
+        match self {
+            Self::Fitd { roll, .. } => match roll.as_ref() {
+                Self::Dice { kq, result } => {
+                    let sixes = if *kq > 0 {
+                        result.iter().filter(|d| **d == 6).count()
+                    } else {
+                        0
+                    };
+                    Some(FitdOutcome::new(roll.result(), sixes))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Pushes a Year Zero roll, rerolling every die except sixes and the
+    /// ones on base and gear dice, and keeps the previous throws.
+    pub fn push(&mut self) -> DiroResult<()> {
+        self.push_with(&mut rand::thread_rng())
+    }
+
+    pub fn push_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> DiroResult<()> {
+        match self {
+            Self::YearZero(rolls) => {
+                let pushed = rolls[rolls.len() - 1].push(rng);
+                rolls.push(pushed);
+                Ok(())
+            }
+            _ => Err(DiroError::NotPushable),
+        }
+    }
+
+    /// The tallied symbols of a narrative pool.
+    pub fn symbols(&self) -> Option<Symbols> {
+        match self {
+            Self::Narrative(faces) => Some(
+                faces
+                    .iter()
+                    .fold(Symbols::default(), |tally, (_, face)| tally + *face),
+            ),
+            _ => None,
+        }
+    }
+
+    /// `detail` followed by the Fate ladder adjective for `FDice`, e.g. `+0-+ (Fair)`.
+    pub fn ladder_detail(&self) -> String {
+        match self {
+            Self::FDice(_) => format!("{} ({})", self.detail(), fate_ladder(self.result())),
+            _ => self.detail(),
         }
     }
 
@@ -128,15 +715,8 @@ impl RollResult {
             Self::D100(v) => v
                 .iter()
                 .map(|r| {
-                    let mut h = r.0[0];
-                    for j in r.2.iter() {
-                        if r.1 && j < &h {
-                            h = *j;
-                        } else if !r.1 && j > &h {
-                            h = *j;
-                        }
-                    }
-                    (h * 10 + r.0[1]) as i32
+                    let tens = d100_chosen(r).map_or(r.0[0], |i| r.2[i]);
+                    d100_value(tens, r.0[1])
                 })
                 .sum(),
             Self::Dice { kq, result } => match kq {
@@ -149,7 +729,7 @@ impl RollResult {
                 i8::MIN..=-1 => {
                     let mut temp = result.clone();
                     temp.sort_unstable();
-                    temp.iter().take(kq.abs() as usize).sum()
+                    temp.iter().take(kq.unsigned_abs() as usize).sum()
                 }
             },
             Self::ADice {
@@ -180,8 +760,157 @@ impl RollResult {
                 count
             }
             Self::FDice(f) => f.iter().sum::<i8>() as i32,
+            Self::Narrative(faces) => {
+                let symbols = self.symbols().unwrap_or_default();
+                // a pure force pool counts light side pips
+                if faces.iter().all(|(die, _)| *die == NarrativeDie::Force) {
+                    symbols.light - symbols.dark
+                } else {
+                    symbols.net_success()
+                }
+            }
+            Self::YearZero(rolls) => rolls[rolls.len() - 1].successes(),
+            Self::Fitd { kind, roll } => match (kind, self.fitd_outcome()) {
+                (FitdKind::Resistance, Some(outcome)) => resistance_stress(roll.result(), outcome),
+                _ => roll.result(),
+            },
+            Self::Savage {
+                traits,
+                wild,
+                modifier,
+                ..
+            } => traits.iter().sum::<i32>().max(wild.iter().sum()) + modifier,
+            Self::RollKeep {
+                chains,
+                kept,
+                bonus,
+                ..
+            } => {
+                let mut totals = chains.iter().map(|c| c.iter().sum()).collect::<Vec<i32>>();
+                totals.sort_unstable();
+                totals.iter().rev().take(*kept as usize).sum::<i32>() + bonus
+            }
+            Self::Cortex(pool) => CortexSelection::best(pool, 2).total(pool),
+            // the margin of success, negative on a failure
+            Self::Gurps { skill, roll } => {
+                let (roll, skill) = (roll.result(), *skill as i32);
+                GurpsOutcome::new(roll, skill).margin(roll, skill)
+            }
+            Self::Shadowrun(rolls) => rolls[rolls.len() - 1].hits(),
+            Self::V5 { .. } => self.v5_outcome().unwrap_or_default().successes,
+            Self::Ironsworn { .. } => self.ironsworn_outcome().map_or(0, |o| o.beaten()),
+        }
+    }
+}
+
+/// The Fate ladder adjective of a result, e.g. `Good` for 3.
+pub fn fate_ladder(value: i32) -> String {
+    const LADDER: [&str; 11] = [
+        "Terrible",
+        "Poor",
+        "Mediocre",
+        "Average",
+        "Fair",
+        "Good",
+        "Great",
+        "Superb",
+        "Fantastic",
+        "Epic",
+        "Legendary",
+    ];
+    match value {
+        i32::MIN..=-3 => format!("{}{}", LADDER[0], value + 2),
+        -2..=8 => LADDER[(value + 2) as usize].to_string(),
+        _ => format!("{}+{}", LADDER[10], value - 8),
+    }
+}
+
+/// Which of the rolled dice are kept by a `k`/`q` rule, in roll order.
+fn kept(kq: i8, result: &[i32]) -> Vec<bool> {
+    let mut kept = vec![kq == 0; result.len()];
+    if kq != 0 {
+        let mut order = (0..result.len()).collect::<Vec<_>>();
+        order.sort_by_key(|i| result[*i]);
+        if kq > 0 {
+            order.reverse();
+        }
+        for i in order.into_iter().take(kq.unsigned_abs() as usize) {
+            kept[i] = true;
+        }
+    }
+    kept
+}
+
+/// A percentile from a tens and a units digit, `00` reads as 100.
+fn d100_value(tens: u8, units: u8) -> i32 {
+    match tens as i32 * 10 + units as i32 {
+        0 => 100,
+        value => value,
+    }
+}
+
+/// The bonus/penalty tens die replacing the rolled tens, if it gives a
+/// lower/higher percentile.
+fn d100_chosen((digits, bonus, tens): &([u8; 2], bool, Vec<u8>)) -> Option<usize> {
+    let mut best = d100_value(digits[0], digits[1]);
+    let mut chosen = None;
+    for (i, t) in tens.iter().enumerate() {
+        let value = d100_value(*t, digits[1]);
+        if (*bonus && value < best) || (!*bonus && value > best) {
+            best = value;
+            chosen = Some(i);
         }
     }
+    chosen
+}
+
+/// Rolls `count` dice, rerolling every die at or above `add_line` in the
+/// following round until no die explodes.
+fn explode<R: Rng + ?Sized>(count: u8, face: u16, add_line: u16, rng: &mut R) -> Vec<Vec<i32>> {
+    let mut result = vec![];
+    let mut add = count;
+
+    loop {
+        let mut next = 0;
+        let mut v = vec![];
+        for _ in 0..add as usize {
+            let t = rng.gen_range(1..=face) as i32;
+            v.push(t);
+            if t >= add_line as i32 {
+                next += 1;
+            }
+        }
+        result.push(v);
+        add = next;
+        if add == 0 {
+            break;
+        }
+    }
+    result
+}
+
+fn render_rounds<R: Renderer + ?Sized>(
+    renderer: &R,
+    result: &[Vec<i32>],
+    mark: impl Fn(i32) -> Mark,
+) -> String {
+    result
+        .iter()
+        .enumerate()
+        .map(|(round, r)| {
+            let mut s = String::new();
+            s.push_str(&renderer.text(&format!("[{}]:", round + 1)));
+            s.push_str(
+                r.iter()
+                    .map(|x| renderer.die(&x.to_string(), mark(*x)))
+                    .collect::<Vec<_>>()
+                    .join(&renderer.text(" "))
+                    .as_str(),
+            );
+            s
+        })
+        .collect::<Vec<_>>()
+        .join(&renderer.text(" "))
 }
 
 impl Default for Dice {
@@ -190,6 +919,7 @@ impl Default for Dice {
     }
 }
 
+#[allow(clippy::self_named_constructors)]
 impl Dice {
     pub fn d100(count: u8, bp: i8) -> DiroResult<Self> {
         Self::_dice(count, 100, bp, 0, 0)
@@ -211,13 +941,115 @@ impl Dice {
             })
         } else if bp != 0 || face == 100 {
             Ok(Dice::D100 { count, bp })
-        } else if kq.abs() as u8 <= count {
+        } else if kq.unsigned_abs() <= count {
             Ok(Dice::Dice { count, face, kq })
         } else {
             Err(DiroError::KQTooBig)
         }
     }
 
+    pub fn advantage(count: u8, face: u16, advantage: bool) -> DiroResult<Self> {
+        if count == 0 {
+            Err(DiroError::NoDice)
+        } else if count > i8::MAX as u8 {
+            // the kept count is stored as an `i8`, which also keeps twice the
+            // count within a `u8`
+            Err(DiroError::KQTooBig)
+        } else {
+            Ok(Dice::Advantage {
+                count,
+                face,
+                advantage,
+            })
+        }
+    }
+
+    /// The dice rolled on a critical hit, every count doubled.
+    pub fn doubled(&self) -> Self {
+        match self.clone() {
+            Self::D100 { count, bp } => Self::D100 {
+                count: count.saturating_mul(2),
+                bp,
+            },
+            Self::Dice { count, face, kq } => Self::Dice {
+                count: count.saturating_mul(2),
+                face,
+                kq: kq.saturating_mul(2),
+            },
+            Self::ADice {
+                count,
+                face,
+                add_line,
+                success_line,
+            } => Self::ADice {
+                count: count.saturating_mul(2),
+                face,
+                add_line,
+                success_line,
+            },
+            Self::CDice {
+                count,
+                face,
+                count_line,
+            } => Self::CDice {
+                count: count.saturating_mul(2),
+                face,
+                count_line,
+            },
+            Self::FDice(count) => Self::FDice(count.saturating_mul(2)),
+            Self::FDice1(count) => Self::FDice1(count.saturating_mul(2)),
+            Self::Fitd { count, kind } => Self::Fitd {
+                count: count.saturating_mul(2),
+                kind,
+            },
+            Self::YearZero { base, skill, gear } => Self::YearZero {
+                base: base.saturating_mul(2),
+                skill: skill.saturating_mul(2),
+                gear: gear.saturating_mul(2),
+            },
+            Self::Narrative(pool) => Self::Narrative(
+                pool.into_iter()
+                    .map(|(count, die)| (count.saturating_mul(2), die))
+                    .collect(),
+            ),
+            Self::Advantage {
+                count,
+                face,
+                advantage,
+            } => Self::Advantage {
+                count: count.saturating_mul(2).min(i8::MAX as u8),
+                face,
+                advantage,
+            },
+            // a trait roll has no count to double
+            Self::Savage { face, modifier } => Self::Savage { face, modifier },
+            Self::RollKeep { rolled, kept } => Self::RollKeep {
+                rolled: rolled.saturating_mul(2),
+                kept: kept.saturating_mul(2),
+            },
+            Self::Gurps(skill) => Self::Gurps(skill),
+            Self::Ironsworn { score, progress } => Self::Ironsworn { score, progress },
+            Self::V5 {
+                pool,
+                hunger,
+                difficulty,
+            } => Self::V5 {
+                pool: pool.saturating_mul(2),
+                hunger: hunger.saturating_mul(2),
+                difficulty,
+            },
+            Self::Shadowrun { count, edge } => Self::Shadowrun {
+                count: count.saturating_mul(2),
+                edge,
+            },
+            Self::Cortex(pool) => Self::Cortex(
+                pool.into_iter()
+                    .map(|(count, face)| (count.saturating_mul(2), face))
+                    .collect(),
+            ),
+        }
+    }
+
     pub fn adice(count: u8, face: u16, success_line: u16, add_line: u16) -> DiroResult<Self> {
         if count == 0 {
             Err(DiroError::NoDice)
@@ -243,6 +1075,59 @@ impl Dice {
         }
     }
 
+    /// A Savage Worlds trait roll, the trait die must be d4-d12.
+    pub fn savage(face: u16, modifier: i32) -> DiroResult<Self> {
+        match face {
+            4 | 6 | 8 | 10 | 12 => Ok(Dice::Savage { face, modifier }),
+            _ => Err(DiroError::InvalidTraitDie(face)),
+        }
+    }
+
+    pub fn roll_keep(rolled: u8, kept: u8) -> DiroResult<Self> {
+        if rolled == 0 {
+            Err(DiroError::NoDice)
+        } else if kept > rolled {
+            Err(DiroError::KQTooBig)
+        } else {
+            Ok(Dice::RollKeep { rolled, kept })
+        }
+    }
+
+    pub fn shadowrun(count: u8, edge: bool) -> DiroResult<Self> {
+        if count == 0 {
+            Err(DiroError::NoDice)
+        } else {
+            Ok(Dice::Shadowrun { count, edge })
+        }
+    }
+
+    /// A V5 pool, every die is a hunger die when `hunger` exceeds `pool`.
+    pub fn v5(pool: u8, hunger: u8, difficulty: Option<u8>) -> DiroResult<Self> {
+        if pool == 0 {
+            Err(DiroError::NoDice)
+        } else {
+            Ok(Dice::V5 {
+                pool,
+                hunger,
+                difficulty,
+            })
+        }
+    }
+
+    /// A Cortex pool, every die must be d4-d12.
+    pub fn cortex(pool: Vec<(u8, u16)>) -> DiroResult<Self> {
+        if pool.iter().all(|(count, _)| *count == 0) {
+            return Err(DiroError::NoDice);
+        }
+        match pool
+            .iter()
+            .find(|(_, face)| ![4, 6, 8, 10, 12].contains(face))
+        {
+            Some((_, face)) => Err(DiroError::InvalidTraitDie(*face)),
+            None => Ok(Dice::Cortex(pool)),
+        }
+    }
+
     pub fn fdice(count: u8) -> DiroResult<Self> {
         if count == 0 {
             Err(DiroError::NoDice)
@@ -252,19 +1137,23 @@ impl Dice {
     }
 
     pub fn roll(&self) -> RollResult {
-        fn range_9(rng: &mut impl Rng) -> u8 {
-            rng.gen_range(0..9)
+        self.roll_with(&mut rand::thread_rng())
+    }
+
+    /// Rolls with the given RNG, e.g. a seeded `StdRng` for reproducible results.
+    pub fn roll_with<R: Rng + ?Sized>(&self, rng: &mut R) -> RollResult {
+        fn digit<R: Rng + ?Sized>(rng: &mut R) -> u8 {
+            rng.gen_range(0..=9)
         }
 
-        let mut rng = rand::thread_rng();
         match self {
             Self::D100 { count, bp } => {
                 let mut r = vec![];
                 for _ in 0..*count as usize {
-                    let result = [range_9(&mut rng), range_9(&mut rng) + 1];
+                    let result = [digit(rng), digit(rng)];
                     let mut bp_result = Vec::new();
-                    for _ in 0..bp.abs() as usize {
-                        bp_result.push(range_9(&mut rng));
+                    for _ in 0..bp.unsigned_abs() as usize {
+                        bp_result.push(digit(rng));
                     }
                     r.push((result, bp > &0, bp_result));
                 }
@@ -278,75 +1167,210 @@ impl Dice {
                 }
                 RollResult::Dice { kq: *kq, result }
             }
-            Self::ADice {
+            Self::Advantage {
                 count,
                 face,
-                add_line,
-                success_line,
+                advantage,
             } => {
-                let mut result = vec![];
-                let mut add = *count;
-
-                loop {
-                    let mut next = 0;
-                    let mut v = vec![];
-                    for _ in 0..add as usize {
-                        let t = rng.gen_range(1..=*face) as i32;
-                        v.push(t);
-                        if t >= *add_line as i32 {
-                            next += 1;
-                        }
-                    }
-                    result.push(v);
-                    add = next;
-                    if add == 0 {
-                        break;
-                    }
-                }
-
-                RollResult::ADice {
-                    result,
-                    add_line: *add_line,
-                    success_line: *success_line,
+                let mut result = Vec::new();
+                for _ in 0..*count as usize * 2 {
+                    result.push(rng.gen_range(1..=*face) as i32);
                 }
+                let kq = if *advantage {
+                    *count as i8
+                } else {
+                    -(*count as i8)
+                };
+                RollResult::Dice { kq, result }
             }
+            Self::ADice {
+                count,
+                face,
+                add_line,
+                success_line,
+            } => RollResult::ADice {
+                result: explode(*count, *face, *add_line, rng),
+                add_line: *add_line,
+                success_line: *success_line,
+            },
             Self::CDice {
                 count,
                 face,
                 count_line,
-            } => {
+            } => RollResult::CDice {
+                result: explode(*count, *face, *count_line, rng),
+                count_line: *count_line,
+            },
+            Self::FDice(count) => {
                 let mut result = vec![];
-                let mut add = *count;
-
-                loop {
-                    let mut next = 0;
-                    let mut v = vec![];
-                    for _ in 0..add as usize {
-                        let t = rng.gen_range(1..=*face) as i32;
-                        v.push(t);
-                        if t >= *count_line as i32 {
-                            next += 1;
-                        }
-                    }
-                    result.push(v);
-                    add = next;
-                    if add == 0 {
-                        break;
-                    }
-                }
-
-                RollResult::CDice {
-                    result,
-                    count_line: *count_line,
+                for _ in 0..*count {
+                    result.push(rng.gen_range(-1..=1));
                 }
+                RollResult::FDice(result)
             }
-            Self::FDice(count) => {
+            Self::FDice1(count) => {
                 let mut result = vec![];
                 for _ in 0..*count {
-                    result.push(rng.gen_range(-1..=1));
+                    result.push(match rng.gen_range(1..=6) {
+                        1 => -1,
+                        6 => 1,
+                        _ => 0,
+                    });
                 }
                 RollResult::FDice(result)
             }
+            Self::Narrative(pool) => {
+                let mut result = vec![];
+                for (count, die) in pool {
+                    let faces = die.faces();
+                    for _ in 0..*count {
+                        let face = faces[rng.gen_range(0..faces.len())];
+                        result.push((*die, Symbols::from_face(face)));
+                    }
+                }
+                RollResult::Narrative(result)
+            }
+            Self::YearZero { base, skill, gear } => {
+                RollResult::YearZero(vec![YearZeroRoll::roll(*base, *skill, *gear, rng)])
+            }
+            Self::Fitd { count, kind } => {
+                let dice = match count {
+                    0 => Self::Dice {
+                        count: 2,
+                        face: 6,
+                        kq: -1,
+                    },
+                    _ => Self::Dice {
+                        count: *count,
+                        face: 6,
+                        kq: 1,
+                    },
+                };
+                RollResult::Fitd {
+                    kind: *kind,
+                    roll: Box::new(dice.roll_with(rng)),
+                }
+            }
+            // a single exploding die rolls one die per round
+            Self::Savage { face, modifier } => RollResult::Savage {
+                face: *face,
+                traits: explode(1, *face, *face, rng).concat(),
+                wild: explode(1, 6, 6, rng).concat(),
+                modifier: *modifier,
+            },
+            Self::RollKeep { rolled, kept } => {
+                let written = (*rolled, *kept);
+                let (rolled, kept, bonus) = ten_dice_rule(*rolled, *kept);
+                RollResult::RollKeep {
+                    chains: (0..rolled)
+                        .map(|_| explode(1, 10, 10, rng).concat())
+                        .collect(),
+                    kept,
+                    bonus,
+                    written,
+                }
+            }
+            Self::Cortex(pool) => {
+                let mut result = vec![];
+                for (count, face) in pool {
+                    for _ in 0..*count {
+                        result.push((*face, rng.gen_range(1..=*face) as i32));
+                    }
+                }
+                RollResult::Cortex(result)
+            }
+            Self::Gurps(skill) => RollResult::Gurps {
+                skill: *skill,
+                roll: Box::new(Self::dice(3, 6, 0).unwrap().roll_with(rng)),
+            },
+            Self::Shadowrun { count, edge } => RollResult::Shadowrun(vec![ShadowrunRoll {
+                pool: *count,
+                edge: *edge,
+                rounds: explode(*count, 6, if *edge { 6 } else { 7 }, rng),
+            }]),
+            Self::Ironsworn { score, progress } => RollResult::Ironsworn {
+                action: (!progress).then(|| rng.gen_range(1..=6)),
+                score: *score,
+                challenge: [rng.gen_range(1..=10), rng.gen_range(1..=10)],
+                momentum: None,
+            },
+            Self::V5 {
+                pool,
+                hunger,
+                difficulty,
+            } => {
+                let hunger = (*hunger).min(*pool);
+                let mut d10 = |count| (0..count).map(|_| rng.gen_range(1..=10)).collect();
+                RollResult::V5 {
+                    regular: d10(pool - hunger),
+                    hunger: d10(hunger),
+                    difficulty: *difficulty,
+                }
+            }
+        }
+    }
+
+    /// The smallest and largest result this dice can roll,
+    /// exploding dice are capped at `i32::MAX`.
+    pub fn bounds(&self) -> (i32, i32) {
+        match self {
+            Self::D100 { count, .. } => (*count as i32, *count as i32 * 100),
+            Self::Dice { count, face, kq } => {
+                let kept = if *kq == 0 {
+                    *count as i32
+                } else {
+                    kq.unsigned_abs() as i32
+                };
+                (kept, kept * *face as i32)
+            }
+            Self::ADice { .. } => (0, i32::MAX),
+            Self::CDice { .. } => (1, i32::MAX),
+            Self::FDice(count) | Self::FDice1(count) => (-(*count as i32), *count as i32),
+            Self::Advantage { count, face, .. } => (*count as i32, *count as i32 * *face as i32),
+            Self::Narrative(pool) => {
+                let force = pool.iter().all(|(_, die)| *die == NarrativeDie::Force);
+                pool.iter().fold((0, 0), |(min, max), (count, die)| {
+                    let values = die.faces().iter().map(|face| {
+                        let s = Symbols::from_face(face);
+                        if force {
+                            s.light - s.dark
+                        } else {
+                            s.net_success()
+                        }
+                    });
+                    let count = *count as i32;
+                    (
+                        min + count * values.clone().min().unwrap(),
+                        max + count * values.max().unwrap(),
+                    )
+                })
+            }
+            Self::YearZero { base, skill, gear } => {
+                (0, *base as i32 + *skill as i32 + *gear as i32)
+            }
+            Self::Fitd {
+                kind: FitdKind::Resistance,
+                ..
+            } => (-1, 5),
+            Self::Fitd { .. } => (1, 6),
+            Self::Savage { modifier, .. } => (1 + modifier, i32::MAX),
+            Self::RollKeep { rolled, kept } => {
+                let (_, kept, bonus) = ten_dice_rule(*rolled, *kept);
+                (kept as i32 + bonus, i32::MAX)
+            }
+            Self::Cortex(pool) => {
+                let mut faces = pool
+                    .iter()
+                    .flat_map(|(count, face)| (0..*count).map(move |_| *face as i32))
+                    .collect::<Vec<_>>();
+                faces.sort_unstable();
+                (0, faces.iter().rev().take(2).sum())
+            }
+            Self::Gurps(skill) => ((*skill as i32 - 18).min(-1), (*skill as i32 - 3).max(0)),
+            Self::Shadowrun { edge: true, .. } => (0, i32::MAX),
+            Self::Shadowrun { count, .. } => (0, *count as i32),
+            Self::V5 { pool, .. } => (0, *pool as i32 + *pool as i32 / 2 * 2),
+            Self::Ironsworn { .. } => (0, 2),
         }
     }
 
@@ -400,7 +1424,77 @@ impl Dice {
                 face,
                 count_line,
             } => format!("{}C{}M{}", count, count_line, face),
-            Self::FDice(f) => format!("{}F3", f),
+            Self::FDice(f) => format!("{}DF", f),
+            Self::FDice1(f) => format!("{}DF.1", f),
+            Self::Narrative(pool) => pool
+                .iter()
+                .map(|(count, die)| format!("{}{}", count, die.letter().to_ascii_uppercase()))
+                .collect(),
+            Self::Fitd { count, kind } => format!(
+                "FITD{}{}",
+                count,
+                match kind {
+                    FitdKind::Action => "",
+                    FitdKind::Resistance => "R",
+                    FitdKind::Fortune => "F",
+                }
+            ),
+            Self::Savage { face, modifier } => match modifier {
+                0 => format!("SWD{}", face),
+                _ => format!("SWD{}{:+}", face, modifier),
+            },
+            Self::RollKeep { rolled, kept } => format!("{}K{}", rolled, kept),
+            Self::Gurps(skill) => format!("GURPS{}", skill),
+            Self::Ironsworn { score, progress } => {
+                format!("IS{}{}", if *progress { "P" } else { "" }, score)
+            }
+            Self::V5 {
+                pool,
+                hunger,
+                difficulty,
+            } => {
+                let mut s = format!("V5 {}", pool);
+                if *hunger > 0 {
+                    s.push_str(&format!("H{}", hunger));
+                }
+                if let Some(difficulty) = difficulty {
+                    s.push_str(&format!(" DIFF{}", difficulty));
+                }
+                s
+            }
+            Self::Shadowrun { count, edge } => {
+                format!("SR{}{}", count, if *edge { "E" } else { "" })
+            }
+            Self::Cortex(pool) => format!(
+                "{{{}}}",
+                pool.iter()
+                    .map(|(count, face)| match count {
+                        1 => format!("D{}", face),
+                        _ => format!("{}D{}", count, face),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            Self::YearZero { base, skill, gear } => {
+                let mut s = format!("YZ{}", base);
+                if *skill > 0 {
+                    s.push_str(&format!("S{}", skill));
+                }
+                if *gear > 0 {
+                    s.push_str(&format!("G{}", gear));
+                }
+                s
+            }
+            Self::Advantage {
+                count,
+                face,
+                advantage,
+            } => format!(
+                "{}D{}{}",
+                count,
+                face,
+                if *advantage { "ADV" } else { "DIS" }
+            ),
         }
     }
 }
@@ -17,4 +17,58 @@ pub enum DiroError {
     NoDice,
     #[error("Dice should roll before calulate")]
     DiceNotRolled,
+    #[error("Can't bound the result of {0} over these operands")]
+    Unbounded(String),
+    #[error("Advantage can't be combined with other dice rules")]
+    AdvantageConflict,
+    #[error("Only Year Zero pools can be pushed")]
+    NotPushable,
+    #[error("Only Shadowrun pools can take a second chance, once")]
+    NoSecondChance,
+    #[error("Only Ironsworn action rolls can burn momentum")]
+    NotBurnable,
+    #[error("Only positive momentum can be burned, not {0}")]
+    NoMomentum(i32),
+    #[error("Invalid table: {0}")]
+    InvalidTable(String),
+    #[error("Unknown table: {0}")]
+    UnknownTable(String),
+    #[error("Tables refer to each other: {0}")]
+    TableCycle(String),
+    #[error("Table {0} has no entry for {1}")]
+    NoTableEntry(String, i32),
+    #[error("Unknown deck: {0}")]
+    UnknownDeck(String),
+    #[error("Deck {0} is empty")]
+    DeckEmpty(String),
+    #[error("{0} is not in hand")]
+    CardNotInHand(String),
+    #[error("Invalid state for deck {0}")]
+    InvalidDeckState(String),
+    #[error("draw({0}) needs a deck, evaluate it with eval_decks")]
+    CardNotDrawn(String),
+    #[error("Can't compute the distribution of {0}")]
+    NoDistribution(String),
+    #[error("Invalid log entry on line {0}: {1}")]
+    InvalidLog(usize, String),
+    #[error("Can't replay a roll made with the {0} RNG")]
+    UnknownRng(String),
+    #[error("Trait die must be d4, d6, d8, d10 or d12, not d{0}")]
+    InvalidTraitDie(u16),
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("Invalid arguments for command .{0}")]
+    InvalidCommand(String),
+    #[error("Age {0} is out of range 15-89")]
+    AgeOutOfRange(u8),
+    #[error("Ability score {0} is out of range")]
+    ScoreOutOfRange(i32),
+    #[error("Point buy spent {0} points, more than 27")]
+    PointBuyOverspent(i32),
+    #[error("Scores are not the standard array")]
+    NotStandardArray,
+    #[error("Expected 6 ability scores, got {0}")]
+    WrongScoreCount(usize),
+    #[error("No set of ability scores reached a total of {0} in {1} rolls")]
+    MinTotalNotReached(i32, u32),
 }
@@ -0,0 +1,166 @@
+//! Verifiable rolls by commit–reveal.
+//!
+//! The bot publishes `commit(server_seed)` before rolling, every roll then
+//! uses a ChaCha20 RNG seeded with SHA-256 of the length-prefixed server
+//! seed, client seed and nonce, and once the server seed is revealed anyone
+//! can check the commitment and recompute every roll with `verify`.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+use crate::error::DiroResult;
+use crate::parse::{parse, DiroAst};
+
+/// A secret server seed and the nonce of the next roll made with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSeed {
+    seed: String,
+    nonce: u64,
+}
+
+/// The SHA-256 of the server seed in lowercase hex.
+pub fn commit(server_seed: &str) -> String {
+    hex(&Sha256::digest(server_seed.as_bytes()))
+}
+
+/// Whether `commitment` was published for `server_seed`.
+pub fn verify_commitment(commitment: &str, server_seed: &str) -> bool {
+    commit(server_seed).eq_ignore_ascii_case(commitment.trim())
+}
+
+/// The RNG of the roll made with `nonce`. Each seed is hashed after its
+/// length as a little-endian u64, so no two pairs of seeds collide.
+pub fn rng(server_seed: &str, client_seed: &str, nonce: u64) -> ChaCha20Rng {
+    let mut hasher = Sha256::new();
+    for seed in [server_seed, client_seed] {
+        hasher.update((seed.len() as u64).to_le_bytes());
+        hasher.update(seed.as_bytes());
+    }
+    hasher.update(nonce.to_le_bytes());
+    ChaCha20Rng::from_seed(hasher.finalize().into())
+}
+
+/// Parses and evaluates `expr` with the RNG of the given seeds.
+pub fn roll(expr: &str, server_seed: &str, client_seed: &str, nonce: u64) -> DiroResult<DiroAst> {
+    let mut ast = parse(expr)?;
+    ast.eval_with(&mut rng(server_seed, client_seed, nonce))?;
+    Ok(ast)
+}
+
+/// Whether rolling `expr` again gives `claimed_detail`, as printed by
+/// `DiroAst::detail_expr`.
+pub fn verify(
+    expr: &str,
+    server_seed: &str,
+    client_seed: &str,
+    nonce: u64,
+    claimed_detail: &str,
+) -> DiroResult<bool> {
+    let ast = roll(expr, server_seed, client_seed, nonce)?;
+    Ok(ast.detail_expr()? == claimed_detail)
+}
+
+/// Like `verify`, but checks every die of `claimed`, the rolled expression
+/// returned by `roll`, which the detail may only show as a subtotal.
+pub fn verify_roll(
+    expr: &str,
+    server_seed: &str,
+    client_seed: &str,
+    nonce: u64,
+    claimed: &DiroAst,
+) -> DiroResult<bool> {
+    Ok(roll(expr, server_seed, client_seed, nonce)? == *claimed)
+}
+
+impl ServerSeed {
+    /// A random 256-bit seed in hex.
+    pub fn new() -> Self {
+        ServerSeed::from_seed(hex(&rand::thread_rng().gen::<[u8; 32]>()))
+    }
+
+    pub fn from_seed<S: Into<String>>(seed: S) -> Self {
+        ServerSeed {
+            seed: seed.into(),
+            nonce: 0,
+        }
+    }
+
+    /// The hash to publish before the first roll.
+    pub fn commitment(&self) -> String {
+        commit(&self.seed)
+    }
+
+    /// The nonce the next roll will use.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Rolls `expr` with the next nonce, returned with the rolled expression.
+    pub fn roll(&mut self, expr: &str, client_seed: &str) -> DiroResult<(u64, DiroAst)> {
+        let nonce = self.nonce;
+        let ast = roll(expr, &self.seed, client_seed, nonce)?;
+        self.nonce += 1;
+        Ok((nonce, ast))
+    }
+
+    /// Ends the use of the seed and returns it for publishing.
+    pub fn reveal(self) -> String {
+        self.seed
+    }
+}
+
+impl Default for ServerSeed {
+    fn default() -> Self {
+        ServerSeed::new()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn fair_test() {
+    assert_eq!(
+        commit("abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    let mut server = ServerSeed::from_seed("server");
+    let commitment = server.commitment();
+    let (nonce, first) = server.roll("3d6+2", "player").unwrap();
+    let (_, second) = server.roll("3d6+2", "player").unwrap();
+    assert_eq!((nonce, server.nonce()), (0, 2));
+
+    let seed = server.reveal();
+    assert!(verify_commitment(&commitment, &seed));
+    assert!(!verify_commitment(&commitment, "other"));
+    let detail = first.detail_expr().unwrap();
+    assert!(verify("3d6+2", &seed, "player", 0, &detail).unwrap());
+    assert!(verify("3d6+2", &seed, "player", 1, &second.detail_expr().unwrap()).unwrap());
+    assert!(verify_roll("3d6+2", &seed, "player", 0, &first).unwrap());
+    assert!(verify_roll("3d6+2", &seed, "player", 1, &second).unwrap());
+    assert!(!verify_roll("3d6+2", &seed, "player", 1, &first).unwrap());
+    assert_ne!(
+        roll("20d20", &seed, "player", 0).unwrap(),
+        roll("20d20", &seed, "other", 0).unwrap()
+    );
+    // the seeds are length-prefixed, so moving a separator changes the roll
+    assert_ne!(
+        roll("20d20", "a:b", "c", 0).unwrap(),
+        roll("20d20", "a", "b:c", 0).unwrap()
+    );
+    assert!(verify("3d6+", &seed, "player", 0, &detail).is_err());
+
+    // the same total from other dice reads the same, but fails `verify_roll`
+    let mut forged = first.clone();
+    if let DiroAst::DyadicOP { lhs, .. } = &mut forged {
+        if let DiroAst::Dice(_, Some(crate::RollResult::Dice { result, .. })) = lhs.as_mut() {
+            result.rotate_left(1);
+        }
+    }
+    assert_ne!(forged, first);
+    assert_eq!(forged.detail_expr().unwrap(), detail);
+    assert!(!verify_roll("3d6+2", &seed, "player", 0, &forged).unwrap());
+}
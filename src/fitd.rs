@@ -0,0 +1,69 @@
+//! Blades in the Dark / Forged in the Dark d6 pools.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FitdKind {
+    /// `fitdN`
+    Action,
+    /// `fitdNr`, the result is the stress cost.
+    Resistance,
+    /// `fitdNf`
+    Fortune,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitdOutcome {
+    /// Two or more sixes.
+    Critical,
+    /// Highest die is a 6.
+    Success,
+    /// Highest die is a 4 or 5.
+    Partial,
+    /// Highest die is 1-3.
+    Failure,
+}
+
+impl FitdOutcome {
+    /// `highest` is the kept die, `sixes` is 0 when rolling zero dice.
+    pub fn new(highest: i32, sixes: usize) -> Self {
+        match highest {
+            6 if sixes >= 2 => Self::Critical,
+            6 => Self::Success,
+            4 | 5 => Self::Partial,
+            _ => Self::Failure,
+        }
+    }
+
+    pub fn describe(&self, kind: FitdKind) -> &'static str {
+        match (kind, self) {
+            (_, Self::Critical) => "critical",
+            (FitdKind::Fortune, Self::Success) => "great",
+            (FitdKind::Fortune, Self::Partial) => "standard",
+            (FitdKind::Fortune, Self::Failure) => "limited",
+            (_, Self::Success) => "full success",
+            (_, Self::Partial) => "partial success",
+            (_, Self::Failure) => "failure",
+        }
+    }
+}
+
+/// Stress cost of a resistance roll, a critical clears 1 stress.
+pub fn resistance_stress(highest: i32, outcome: FitdOutcome) -> i32 {
+    match outcome {
+        FitdOutcome::Critical => -1,
+        _ => 6 - highest,
+    }
+}
+
+#[test]
+fn fitd_test() {
+    assert_eq!(FitdOutcome::new(6, 2), FitdOutcome::Critical);
+    assert_eq!(FitdOutcome::new(6, 1), FitdOutcome::Success);
+    assert_eq!(FitdOutcome::new(6, 0), FitdOutcome::Success);
+    assert_eq!(FitdOutcome::new(5, 0), FitdOutcome::Partial);
+    assert_eq!(FitdOutcome::new(4, 0), FitdOutcome::Partial);
+    assert_eq!(FitdOutcome::new(3, 0), FitdOutcome::Failure);
+    assert_eq!(resistance_stress(6, FitdOutcome::Critical), -1);
+    assert_eq!(resistance_stress(2, FitdOutcome::Failure), 4);
+    assert_eq!(FitdOutcome::Partial.describe(FitdKind::Fortune), "standard");
+}
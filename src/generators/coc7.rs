@@ -0,0 +1,255 @@
+//! Call of Cthulhu 7e investigator characteristics.
+
+use rand::Rng;
+
+use crate::coc::{grow_with, Growth};
+use crate::error::{DiroError, DiroResult};
+use crate::{parse, Dice, DiroAst};
+
+/// A rolled characteristic with the way it was reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stat {
+    pub value: i32,
+    /// e.g. `3D6*5 = 12*5 = 60, age -5 = 55`
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Investigator {
+    pub age: Option<u8>,
+    pub str: Stat,
+    pub con: Stat,
+    pub siz: Stat,
+    pub dex: Stat,
+    pub app: Stat,
+    pub int: Stat,
+    pub pow: Stat,
+    pub edu: Stat,
+    pub luck: Stat,
+    pub hp: i32,
+    pub mp: i32,
+    pub san: i32,
+    pub mov: i32,
+    /// `-2`, `-1`, `0` or dice such as `1D4`.
+    pub damage_bonus: DiroAst,
+    pub build: i32,
+    pub edu_checks: Vec<Growth>,
+}
+
+impl Stat {
+    fn roll<R: Rng + ?Sized>(source: &str, rng: &mut R) -> DiroResult<Self> {
+        let mut ast = parse(source)?;
+        let value = ast.eval_with(rng)?;
+        Ok(Stat {
+            value,
+            detail: format!("{} = {} = {}", ast.expr(), ast.detail_expr()?, value),
+        })
+    }
+
+    fn adjust(&mut self, delta: i32, reason: &str) {
+        let value = (self.value + delta).clamp(1, 99);
+        self.detail.push_str(&format!(
+            ", {} {:+} = {}",
+            reason,
+            value - self.value,
+            value
+        ));
+        self.value = value;
+    }
+}
+
+/// Spreads a deduction evenly over the given characteristics.
+fn deduct(total: i32, stats: &mut [&mut Stat]) {
+    let n = stats.len() as i32;
+    for (i, stat) in stats.iter_mut().enumerate() {
+        let share = total / n + i32::from((i as i32) < total % n);
+        if share > 0 {
+            stat.adjust(-share, "age");
+        }
+    }
+}
+
+/// EDU improvement checks, STR/CON/DEX deduction, APP deduction and MOV
+/// penalty of an age bracket.
+fn age_bracket(age: u8) -> DiroResult<(u8, i32, i32, i32)> {
+    match age {
+        15..=19 => Ok((0, 0, 0, 0)),
+        20..=39 => Ok((1, 0, 0, 0)),
+        40..=49 => Ok((2, 5, 5, 1)),
+        50..=59 => Ok((3, 10, 10, 2)),
+        60..=69 => Ok((4, 20, 15, 3)),
+        70..=79 => Ok((4, 40, 20, 4)),
+        80..=89 => Ok((4, 80, 25, 5)),
+        _ => Err(DiroError::AgeOutOfRange(age)),
+    }
+}
+
+/// Damage bonus and build from STR+SIZ.
+fn damage_bonus(str_siz: i32) -> DiroResult<(DiroAst, i32)> {
+    Ok(match str_siz {
+        i32::MIN..=64 => (DiroAst::Int(-2), -2),
+        65..=84 => (DiroAst::Int(-1), -1),
+        85..=124 => (DiroAst::Int(0), 0),
+        125..=164 => (DiroAst::Dice(Dice::dice(1, 4, 0)?, None), 1),
+        165..=204 => (DiroAst::Dice(Dice::dice(1, 6, 0)?, None), 2),
+        _ => {
+            let count = (str_siz - 205) / 80 + 2;
+            (
+                DiroAst::Dice(Dice::dice(count as u8, 6, 0)?, None),
+                count + 1,
+            )
+        }
+    })
+}
+
+/// Generates an investigator, applying the age modifiers when an age is given.
+pub fn generate(age: Option<u8>) -> DiroResult<Investigator> {
+    generate_with(age, &mut rand::thread_rng())
+}
+
+pub fn generate_with<R: Rng + ?Sized>(age: Option<u8>, rng: &mut R) -> DiroResult<Investigator> {
+    let (edu_checks, physical, app_penalty, mov_penalty) = match age {
+        Some(age) => age_bracket(age)?,
+        None => (0, 0, 0, 0),
+    };
+    let mut str = Stat::roll("3d6*5", rng)?;
+    let mut con = Stat::roll("3d6*5", rng)?;
+    let mut siz = Stat::roll("(2d6+6)*5", rng)?;
+    let mut dex = Stat::roll("3d6*5", rng)?;
+    let mut app = Stat::roll("3d6*5", rng)?;
+    let int = Stat::roll("(2d6+6)*5", rng)?;
+    let pow = Stat::roll("3d6*5", rng)?;
+    let mut edu = Stat::roll("(2d6+6)*5", rng)?;
+    let mut luck = Stat::roll("3d6*5", rng)?;
+
+    if let Some(15..=19) = age {
+        deduct(5, &mut [&mut str, &mut siz]);
+        edu.adjust(-5, "age");
+        let again = Stat::roll("3d6*5", rng)?;
+        luck.detail.push_str(&format!(", {}", again.detail));
+        luck.value = luck.value.max(again.value);
+        luck.detail.push_str(&format!(", higher = {}", luck.value));
+    }
+    deduct(physical, &mut [&mut str, &mut con, &mut dex]);
+    if app_penalty > 0 {
+        app.adjust(-app_penalty, "age");
+    }
+    let mut checks = vec![];
+    for _ in 0..edu_checks {
+        let growth = grow_with([("EDU", edu.value)], rng).remove(0);
+        if growth.success() {
+            edu.adjust(growth.after - growth.before, "EDU check");
+        }
+        checks.push(growth);
+    }
+
+    let mov = if dex.value < siz.value && str.value < siz.value {
+        7
+    } else if dex.value > siz.value && str.value > siz.value {
+        9
+    } else {
+        8
+    } - mov_penalty;
+    let (damage_bonus, build) = damage_bonus(str.value + siz.value)?;
+    Ok(Investigator {
+        age,
+        hp: (con.value + siz.value) / 10,
+        mp: pow.value / 5,
+        san: pow.value,
+        mov,
+        damage_bonus,
+        build,
+        edu_checks: checks,
+        str,
+        con,
+        siz,
+        dex,
+        app,
+        int,
+        pow,
+        edu,
+        luck,
+    })
+}
+
+impl Investigator {
+    /// The total of the eight characteristics, without LUCK.
+    pub fn total(&self) -> i32 {
+        [
+            &self.str, &self.con, &self.siz, &self.dex, &self.app, &self.int, &self.pow, &self.edu,
+        ]
+        .iter()
+        .map(|s| s.value)
+        .sum()
+    }
+
+    /// One line per characteristic, followed by the derived attributes.
+    pub fn detail(&self) -> String {
+        let mut lines = [
+            ("STR", &self.str),
+            ("CON", &self.con),
+            ("SIZ", &self.siz),
+            ("DEX", &self.dex),
+            ("APP", &self.app),
+            ("INT", &self.int),
+            ("POW", &self.pow),
+            ("EDU", &self.edu),
+            ("LUCK", &self.luck),
+        ]
+        .iter()
+        .map(|(name, stat)| format!("{}: {}", name, stat.detail))
+        .collect::<Vec<_>>();
+        let damage_bonus = match &self.damage_bonus {
+            DiroAst::Int(_) => self.damage_bonus.expr(),
+            ast => format!("+{}", ast.expr()),
+        };
+        lines.push(format!(
+            "HP: {} MP: {} SAN: {} MOV: {} DB: {} Build: {} Total: {}",
+            self.hp,
+            self.mp,
+            self.san,
+            self.mov,
+            damage_bonus,
+            self.build,
+            self.total()
+        ));
+        lines.join("\n")
+    }
+}
+
+#[test]
+fn coc7_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(7);
+    for age in [None, Some(17), Some(25), Some(45), Some(85)] {
+        let inv = generate_with(age, &mut rng).unwrap();
+        for stat in [&inv.str, &inv.con, &inv.dex, &inv.app, &inv.pow] {
+            assert!((1..=90).contains(&stat.value), "{}", stat.detail);
+        }
+        for stat in [&inv.siz, &inv.int, &inv.edu] {
+            assert!((1..=99).contains(&stat.value), "{}", stat.detail);
+        }
+        assert_eq!(inv.hp, (inv.con.value + inv.siz.value) / 10);
+        assert_eq!(inv.san, inv.pow.value);
+        let detail = inv.detail();
+        assert_eq!(detail.lines().count(), 10);
+        assert!(detail.starts_with(&format!("STR: {}", inv.str.detail)));
+        assert!(detail.ends_with(&format!("Total: {}", inv.total())));
+    }
+    assert_eq!(
+        generate_with(Some(25), &mut rng).unwrap().edu_checks.len(),
+        1
+    );
+    assert_eq!(
+        generate_with(Some(85), &mut rng).unwrap().edu_checks.len(),
+        4
+    );
+    assert!(generate_with(Some(12), &mut rng).is_err());
+
+    assert_eq!(damage_bonus(64).unwrap(), (DiroAst::Int(-2), -2));
+    assert_eq!(damage_bonus(124).unwrap(), (DiroAst::Int(0), 0));
+    assert_eq!(damage_bonus(125).unwrap().0.expr(), "1D4");
+    assert_eq!(damage_bonus(284).unwrap(), (parse("2d6").unwrap(), 3));
+    assert_eq!(damage_bonus(285).unwrap(), (parse("3d6").unwrap(), 4));
+}
@@ -0,0 +1,223 @@
+//! D&D 5e ability scores.
+
+use rand::Rng;
+
+use crate::error::{DiroError, DiroResult};
+use crate::render::Renderer;
+use crate::{Dice, RollResult};
+
+pub const STANDARD_ARRAY: [i32; 6] = [15, 14, 13, 12, 10, 8];
+pub const POINT_BUY_POINTS: i32 = 27;
+/// How many sets `roll` tries before giving up on the minimum total.
+pub const MAX_ROLLS: u32 = 1000;
+
+/// One `4d6k3` ability score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub roll: RollResult,
+    pub value: i32,
+}
+
+/// Six rolled ability scores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbilityScores {
+    pub scores: Vec<Score>,
+    /// How many sets were rerolled for totalling below the minimum.
+    pub rerolls: u32,
+}
+
+/// The ability modifier, `(score - 10) / 2` rounded down.
+pub fn modifier(score: i32) -> i32 {
+    (score - 10).div_euclid(2)
+}
+
+impl Score {
+    pub fn modifier(&self) -> i32 {
+        modifier(self.value)
+    }
+
+    /// The dice dropped by `k3`.
+    pub fn dropped(&self) -> Vec<i32> {
+        match &self.roll {
+            RollResult::Dice { kq, result } => {
+                let mut temp = result.clone();
+                temp.sort_unstable();
+                temp.into_iter()
+                    .take(result.len() - kq.unsigned_abs() as usize)
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// e.g. `4D6K3 = 2+5+1+6 = 13 (+1), dropped 1`
+    pub fn detail(&self) -> String {
+        format!(
+            "4D6K3 = {} = {} ({:+}), dropped {}",
+            self.roll.detail(),
+            self.value,
+            self.modifier(),
+            self.dropped()
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    /// Renders `4D6K3 = dice = score (modifier)`, where the renderer marks the dropped die.
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> String {
+        format!(
+            "{}{}{}{}{}",
+            renderer.text("4D6K3 = "),
+            self.roll.render(renderer),
+            renderer.text(" = "),
+            renderer.total(self.value),
+            renderer.text(&format!(" ({:+})", self.modifier()))
+        )
+    }
+}
+
+impl AbilityScores {
+    pub fn total(&self) -> i32 {
+        self.scores.iter().map(|s| s.value).sum()
+    }
+
+    pub fn detail(&self) -> String {
+        self.scores
+            .iter()
+            .map(|s| s.detail())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Rolls `6#4d6k3`, rerolling the whole set while it totals below `min_total`,
+/// up to `MAX_ROLLS` sets.
+pub fn roll(min_total: Option<i32>) -> DiroResult<AbilityScores> {
+    roll_with(min_total, &mut rand::thread_rng())
+}
+
+pub fn roll_with<R: Rng + ?Sized>(
+    min_total: Option<i32>,
+    rng: &mut R,
+) -> DiroResult<AbilityScores> {
+    let min_total = min_total.unwrap_or(0);
+    if min_total > 18 * 6 {
+        return Err(DiroError::ScoreOutOfRange(min_total));
+    }
+    let dice = Dice::dice(4, 6, 3)?;
+    for rerolls in 0..MAX_ROLLS {
+        let scores = (0..6)
+            .map(|_| {
+                let roll = dice.roll_with(rng);
+                Score {
+                    value: roll.result(),
+                    roll,
+                }
+            })
+            .collect();
+        let set = AbilityScores { scores, rerolls };
+        if set.total() >= min_total {
+            return Ok(set);
+        }
+    }
+    Err(DiroError::MinTotalNotReached(min_total, MAX_ROLLS))
+}
+
+/// Checks that the scores are a permutation of the standard array.
+pub fn validate_standard_array(scores: &[i32]) -> DiroResult<()> {
+    let mut sorted = scores.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    if sorted == STANDARD_ARRAY {
+        Ok(())
+    } else {
+        Err(DiroError::NotStandardArray)
+    }
+}
+
+/// The point-buy cost of a single score between 8 and 15.
+pub fn point_buy_cost(score: i32) -> DiroResult<i32> {
+    match score {
+        8..=13 => Ok(score - 8),
+        14 => Ok(7),
+        15 => Ok(9),
+        _ => Err(DiroError::ScoreOutOfRange(score)),
+    }
+}
+
+/// Validates a point-buy array of six scores and returns the points left.
+pub fn validate_point_buy(scores: &[i32]) -> DiroResult<i32> {
+    if scores.len() != STANDARD_ARRAY.len() {
+        return Err(DiroError::WrongScoreCount(scores.len()));
+    }
+    let mut spent = 0;
+    for score in scores {
+        spent += point_buy_cost(*score)?;
+    }
+    if spent > POINT_BUY_POINTS {
+        Err(DiroError::PointBuyOverspent(spent))
+    } else {
+        Ok(POINT_BUY_POINTS - spent)
+    }
+}
+
+#[test]
+fn dnd5e_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let score = Score {
+        roll: RollResult::Dice {
+            kq: 3,
+            result: vec![2, 5, 1, 6],
+        },
+        value: 13,
+    };
+    assert_eq!(score.detail(), "4D6K3 = 2+5+1+6 = 13 (+1), dropped 1");
+    assert_eq!(
+        score.render(&crate::Markdown),
+        "4D6K3 = 2+5+~~1~~+6 = **13** (+1)"
+    );
+
+    for (score, m) in [
+        (1, -5),
+        (8, -1),
+        (9, -1),
+        (10, 0),
+        (11, 0),
+        (15, 2),
+        (20, 5),
+    ] {
+        assert_eq!(modifier(score), m, "{}", score);
+    }
+
+    let mut rng = StdRng::seed_from_u64(5);
+    let set = roll_with(Some(80), &mut rng).unwrap();
+    assert_eq!(set.scores.len(), 6);
+    assert!(set.total() >= 80);
+    assert!(set.scores.iter().all(|s| (3..=18).contains(&s.value)));
+    assert!(roll_with(Some(109), &mut rng).is_err());
+    // a total of 100 is too rare to ever be reached
+    assert!(matches!(
+        roll_with(Some(100), &mut rng),
+        Err(DiroError::MinTotalNotReached(100, MAX_ROLLS))
+    ));
+
+    assert!(validate_standard_array(&[8, 10, 12, 13, 14, 15]).is_ok());
+    assert!(validate_standard_array(&[8, 10, 12, 13, 15, 15]).is_err());
+
+    assert_eq!(validate_point_buy(&[15, 15, 15, 8, 8, 8]).unwrap(), 0);
+    assert_eq!(validate_point_buy(&[13, 13, 13, 12, 12, 12]).unwrap(), 0);
+    assert_eq!(validate_point_buy(&[8, 8, 8, 8, 8, 8]).unwrap(), 27);
+    assert!(validate_point_buy(&[15, 15, 15, 9, 8, 8]).is_err());
+    assert!(validate_point_buy(&[16, 8, 8, 8, 8, 8]).is_err());
+    assert!(validate_point_buy(&[7, 8, 8, 8, 8, 8]).is_err());
+    assert!(matches!(
+        validate_point_buy(&[8, 8, 8]),
+        Err(DiroError::WrongScoreCount(3))
+    ));
+    assert!(matches!(
+        validate_point_buy(&[8; 7]),
+        Err(DiroError::WrongScoreCount(7))
+    ));
+}
@@ -0,0 +1,4 @@
+//! Character generators for `.coc` / `.dnd`.
+
+pub mod coc7;
+pub mod dnd5e;
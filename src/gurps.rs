@@ -0,0 +1,99 @@
+//! GURPS 3d6 roll-under success rolls.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GurpsOutcome {
+    CriticalSuccess,
+    Success,
+    Failure,
+    CriticalFailure,
+}
+
+impl GurpsOutcome {
+    /// `roll` is the 3d6 total, `skill` the effective skill.
+    pub fn new(roll: i32, skill: i32) -> Self {
+        let critical_success = match skill {
+            16.. => 6,
+            15 => 5,
+            _ => 4,
+        };
+        if roll <= critical_success {
+            Self::CriticalSuccess
+        } else if roll == 18 || (roll == 17 && skill <= 15) || roll - skill >= 10 {
+            Self::CriticalFailure
+        } else if roll <= skill && roll < 17 {
+            Self::Success
+        } else {
+            Self::Failure
+        }
+    }
+
+    /// The margin `skill - roll`, at least 0 on an automatic success and
+    /// at most -1 on an automatic failure such as 17 vs 20.
+    pub fn margin(&self, roll: i32, skill: i32) -> i32 {
+        match self {
+            Self::CriticalSuccess | Self::Success => (skill - roll).max(0),
+            Self::Failure | Self::CriticalFailure => (skill - roll).min(-1),
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::CriticalSuccess => "critical success",
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::CriticalFailure => "critical failure",
+        }
+    }
+}
+
+#[test]
+fn gurps_test() {
+    use GurpsOutcome::*;
+
+    for (roll, skill, outcome) in [
+        // 3-4 always critically succeed, even at skill 3
+        (3, 3, CriticalSuccess),
+        (4, 3, CriticalSuccess),
+        (4, 14, CriticalSuccess),
+        (5, 14, Success),
+        (5, 15, CriticalSuccess),
+        (6, 15, Success),
+        (6, 16, CriticalSuccess),
+        (7, 16, Success),
+        (6, 20, CriticalSuccess),
+        (7, 20, Success),
+        // roll-under
+        (12, 12, Success),
+        (13, 12, Failure),
+        (5, 4, Failure),
+        // missing by 10 or more
+        (13, 4, Failure),
+        (14, 4, CriticalFailure),
+        (15, 5, CriticalFailure),
+        (16, 7, Failure),
+        // 17 and 18
+        (16, 16, Success),
+        (17, 15, CriticalFailure),
+        (17, 16, Failure),
+        (17, 20, Failure),
+        (18, 20, CriticalFailure),
+    ] {
+        assert_eq!(
+            GurpsOutcome::new(roll, skill),
+            outcome,
+            "{} vs {}",
+            roll,
+            skill
+        );
+    }
+
+    for (roll, skill, margin) in [
+        (11, 12, 1),
+        (13, 12, -1),
+        (4, 3, 0),
+        (17, 20, -1),
+        (18, 20, -1),
+    ] {
+        assert_eq!(GurpsOutcome::new(roll, skill).margin(roll, skill), margin);
+    }
+}
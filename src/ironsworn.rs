@@ -0,0 +1,70 @@
+//! Ironsworn action and progress rolls against two challenge dice.
+
+/// The action score can't go above 10.
+pub const MAX_SCORE: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IronswornHit {
+    /// The score beats both challenge dice.
+    Strong,
+    /// The score beats one challenge die.
+    Weak,
+    Miss,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IronswornOutcome {
+    pub hit: IronswornHit,
+    /// Both challenge dice show the same number.
+    pub matched: bool,
+}
+
+impl IronswornOutcome {
+    pub fn new(score: i32, challenge: [i32; 2]) -> Self {
+        let beaten = challenge.iter().filter(|c| score > **c).count();
+        IronswornOutcome {
+            hit: match beaten {
+                2 => IronswornHit::Strong,
+                1 => IronswornHit::Weak,
+                _ => IronswornHit::Miss,
+            },
+            matched: challenge[0] == challenge[1],
+        }
+    }
+
+    /// The number of challenge dice beaten.
+    pub fn beaten(&self) -> i32 {
+        match self.hit {
+            IronswornHit::Strong => 2,
+            IronswornHit::Weak => 1,
+            IronswornHit::Miss => 0,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let hit = match self.hit {
+            IronswornHit::Strong => "strong hit",
+            IronswornHit::Weak => "weak hit",
+            IronswornHit::Miss => "miss",
+        };
+        if self.matched {
+            format!("{}, match", hit)
+        } else {
+            hit.to_string()
+        }
+    }
+}
+
+#[test]
+fn ironsworn_test() {
+    let o = IronswornOutcome::new(7, [3, 6]);
+    assert_eq!(o.hit, IronswornHit::Strong);
+    assert_eq!(o.describe(), "strong hit");
+
+    // ties go to the challenge dice
+    let o = IronswornOutcome::new(6, [6, 2]);
+    assert_eq!((o.hit, o.beaten()), (IronswornHit::Weak, 1));
+
+    let o = IronswornOutcome::new(10, [10, 10]);
+    assert_eq!(o.describe(), "miss, match");
+}
@@ -0,0 +1,30 @@
+//! Legend of the Five Rings roll-and-keep dice.
+
+/// The most dice that can be rolled or kept.
+pub const MAX_DICE: u8 = 10;
+
+/// Applies the Ten Dice Rule and returns the dice to roll, the dice to keep
+/// and the flat bonus.
+///
+/// Every two rolled dice above ten become one kept die, an odd one left over
+/// is lost. Every kept die above ten then becomes a +2 bonus.
+pub fn ten_dice_rule(rolled: u8, kept: u8) -> (u8, u8, i32) {
+    let mut kept = kept as i32;
+    let mut rolled = rolled as i32;
+    if rolled > MAX_DICE as i32 {
+        kept += (rolled - MAX_DICE as i32) / 2;
+        rolled = MAX_DICE as i32;
+    }
+    let bonus = (kept - MAX_DICE as i32).max(0) * 2;
+    (rolled as u8, kept.min(MAX_DICE as i32) as u8, bonus)
+}
+
+#[test]
+fn l5r_test() {
+    assert_eq!(ten_dice_rule(7, 3), (7, 3, 0));
+    assert_eq!(ten_dice_rule(10, 10), (10, 10, 0));
+    assert_eq!(ten_dice_rule(12, 4), (10, 5, 0));
+    assert_eq!(ten_dice_rule(13, 4), (10, 5, 0));
+    assert_eq!(ten_dice_rule(14, 9), (10, 10, 2));
+    assert_eq!(ten_dice_rule(10, 12), (10, 10, 4));
+}
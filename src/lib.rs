@@ -1,9 +1,32 @@
+mod bands;
+pub mod coc;
+pub mod command;
+pub mod cortex;
+pub mod deck;
+pub mod diagnostics;
 mod dice;
 mod error;
+#[cfg(feature = "fair")]
+pub mod fair;
+pub mod fitd;
+pub mod generators;
+pub mod gurps;
+pub mod ironsworn;
+pub mod l5r;
+pub mod narrative;
 mod parse;
+mod render;
+pub mod savage;
+pub mod session;
+pub mod shadowrun;
+pub mod tables;
 #[cfg(test)]
 mod tests;
+pub mod v5;
+pub mod yearzero;
 
-pub use dice::{Dice, RollResult};
+pub use bands::{Bands, Tier};
+pub use dice::{fate_ladder, Dice, RollResult};
 pub use error::{DiroError, DiroResult};
-pub use parse::{parse, DiroAst};
+pub use parse::{parse, DiroAst, Outcome, Verb};
+pub use render::{Ansi, BBCode, Html, Mark, Markdown, Plain, Renderer};
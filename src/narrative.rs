@@ -0,0 +1,202 @@
+//! Genesys / Star Wars narrative dice.
+//!
+//! Faces are written with one letter per symbol: `S` success, `A` advantage,
+//! `R` triumph, `F` failure, `T` threat, `D` despair, `L` light side and
+//! `N` dark side, `-` is a blank face.
+
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NarrativeDie {
+    /// `b`, blue d6
+    Boost,
+    /// `k`, black d6
+    Setback,
+    /// `g`, green d8
+    Ability,
+    /// `p`, purple d8
+    Difficulty,
+    /// `y`, yellow d12
+    Proficiency,
+    /// `r`, red d12
+    Challenge,
+    /// `w`, white d12
+    Force,
+}
+
+/// Symbols shown on one face, or tallied over a whole pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbols {
+    pub success: i32,
+    pub failure: i32,
+    pub advantage: i32,
+    pub threat: i32,
+    pub triumph: i32,
+    pub despair: i32,
+    pub light: i32,
+    pub dark: i32,
+}
+
+impl NarrativeDie {
+    pub fn from_letter(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            'b' => Some(Self::Boost),
+            'k' => Some(Self::Setback),
+            'g' => Some(Self::Ability),
+            'p' => Some(Self::Difficulty),
+            'y' => Some(Self::Proficiency),
+            'r' => Some(Self::Challenge),
+            'w' => Some(Self::Force),
+            _ => None,
+        }
+    }
+
+    pub fn letter(&self) -> char {
+        match self {
+            Self::Boost => 'b',
+            Self::Setback => 'k',
+            Self::Ability => 'g',
+            Self::Difficulty => 'p',
+            Self::Proficiency => 'y',
+            Self::Challenge => 'r',
+            Self::Force => 'w',
+        }
+    }
+
+    pub fn faces(&self) -> &'static [&'static str] {
+        match self {
+            Self::Boost => &["", "", "S", "SA", "AA", "A"],
+            Self::Setback => &["", "", "F", "F", "T", "T"],
+            Self::Ability => &["", "S", "S", "SS", "A", "A", "SA", "AA"],
+            Self::Difficulty => &["", "F", "FF", "T", "T", "T", "TT", "FT"],
+            Self::Proficiency => &[
+                "", "S", "S", "SS", "SS", "A", "SA", "SA", "SA", "AA", "AA", "R",
+            ],
+            Self::Challenge => &[
+                "", "F", "F", "FF", "FF", "T", "T", "FT", "FT", "TT", "TT", "D",
+            ],
+            Self::Force => &[
+                "N", "N", "N", "N", "N", "N", "NN", "L", "L", "LL", "LL", "LL",
+            ],
+        }
+    }
+}
+
+impl Symbols {
+    pub fn from_face(face: &str) -> Self {
+        let mut s = Symbols::default();
+        for c in face.chars() {
+            match c {
+                'S' => s.success += 1,
+                'F' => s.failure += 1,
+                'A' => s.advantage += 1,
+                'T' => s.threat += 1,
+                'R' => s.triumph += 1,
+                'D' => s.despair += 1,
+                'L' => s.light += 1,
+                'N' => s.dark += 1,
+                _ => {}
+            }
+        }
+        s
+    }
+
+    /// Successes minus failures, where a triumph is also a success and a
+    /// despair is also a failure.
+    pub fn net_success(&self) -> i32 {
+        self.success + self.triumph - self.failure - self.despair
+    }
+
+    pub fn net_advantage(&self) -> i32 {
+        self.advantage - self.threat
+    }
+
+    pub fn face(&self) -> String {
+        let mut s = String::new();
+        for (c, n) in [
+            ('S', self.success),
+            ('A', self.advantage),
+            ('R', self.triumph),
+            ('F', self.failure),
+            ('T', self.threat),
+            ('D', self.despair),
+            ('L', self.light),
+            ('N', self.dark),
+        ] {
+            for _ in 0..n {
+                s.push(c);
+            }
+        }
+        if s.is_empty() {
+            s.push('-');
+        }
+        s
+    }
+}
+
+impl std::ops::Add for Symbols {
+    type Output = Symbols;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Symbols {
+            success: self.success + rhs.success,
+            failure: self.failure + rhs.failure,
+            advantage: self.advantage + rhs.advantage,
+            threat: self.threat + rhs.threat,
+            triumph: self.triumph + rhs.triumph,
+            despair: self.despair + rhs.despair,
+            light: self.light + rhs.light,
+            dark: self.dark + rhs.dark,
+        }
+    }
+}
+
+/// The net outcome after cancellation, e.g. `1 success, 2 advantage, 1 triumph`.
+impl Display for Symbols {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let success = self.net_success();
+        let advantage = self.net_advantage();
+        let mut parts = vec![if success > 0 {
+            format!("{} success", success)
+        } else {
+            format!("{} failure", -success)
+        }];
+        if advantage > 0 {
+            parts.push(format!("{} advantage", advantage));
+        } else if advantage < 0 {
+            parts.push(format!("{} threat", -advantage));
+        }
+        for (n, name) in [
+            (self.triumph, "triumph"),
+            (self.despair, "despair"),
+            (self.light, "light"),
+            (self.dark, "dark"),
+        ] {
+            if n > 0 {
+                parts.push(format!("{} {}", n, name));
+            }
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[test]
+fn narrative_test() {
+    let tally = ["SA", "R", "FT", "TT", "D"]
+        .iter()
+        .map(|f| Symbols::from_face(f))
+        .fold(Symbols::default(), |a, b| a + b);
+    assert_eq!(tally.net_success(), 0);
+    assert_eq!(tally.net_advantage(), -2);
+    assert_eq!(
+        tally.to_string(),
+        "0 failure, 2 threat, 1 triumph, 1 despair"
+    );
+    assert_eq!(Symbols::from_face("").face(), "-");
+    assert_eq!(Symbols::from_face("AS").face(), "SA");
+    for c in "bkgpyrw".chars() {
+        assert_eq!(NarrativeDie::from_letter(c).unwrap().letter(), c);
+    }
+}
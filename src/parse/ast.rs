@@ -1,11 +1,18 @@
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
+use rand::Rng;
+
 use crate::{
+    bands::{Bands, Tier},
+    deck::{Card, Decks},
     error::{DiroError, DiroResult},
+    render::{Mark, Plain, Renderer},
     Dice, RollResult,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DiroAst {
     Int(i32),
     Dice(Dice, Option<RollResult>),
@@ -15,9 +22,33 @@ pub enum DiroAst {
         rhs: Box<DiroAst>,
     },
     Closed(Box<DiroAst>),
+    /// The result of `ast` mapped into a tier, e.g. `pbta(2d6+1)`.
+    Banded {
+        ast: Box<DiroAst>,
+        bands: Bands,
+    },
+    /// `draw(init)`, a card drawn from a deck by `draw_decks`.
+    Draw {
+        deck: String,
+        card: Option<Card>,
+    },
+}
+
+/// Flags of an evaluated expression.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outcome {
+    /// The kept face of the first single d20, before any modifier.
+    pub natural: Option<i32>,
+    /// Natural 20.
+    pub critical: bool,
+    /// Natural 1.
+    pub fumble: bool,
+    /// The tier of the outermost banded expression.
+    pub tier: Option<Tier>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Verb {
     Plus,   // +
     Minus,  // -
@@ -28,18 +59,277 @@ pub enum Verb {
 }
 
 impl DiroAst {
+    /// Rolls and calculates the expression, `draw(deck)` needs `eval_decks`.
     pub fn eval(&mut self) -> DiroResult<i32> {
         self.roll();
         self.calc()
     }
 
+    /// Like `eval`, but rolls with the given RNG.
+    pub fn eval_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> DiroResult<i32> {
+        self.roll_with(rng);
+        self.calc()
+    }
+
+    /// The Fate ladder adjective of the result, e.g. `4dF+2`.
+    pub fn ladder(&self) -> DiroResult<String> {
+        Ok(crate::dice::fate_ladder(self.calc()?))
+    }
+
+    /// Like `eval`, but also returns the outcome flags.
+    pub fn eval_outcome(&mut self) -> DiroResult<(i32, Outcome)> {
+        let result = self.eval()?;
+        Ok((result, self.outcome()?))
+    }
+
+    pub fn outcome(&self) -> DiroResult<Outcome> {
+        let natural = self.natural()?;
+        Ok(Outcome {
+            natural,
+            critical: natural == Some(20),
+            fumble: natural == Some(1),
+            tier: self.tier()?,
+        })
+    }
+
+    /// Wraps the expression so its result is mapped into `bands`.
+    pub fn banded(self, bands: Bands) -> DiroAst {
+        DiroAst::Banded {
+            ast: Box::new(self),
+            bands,
+        }
+    }
+
+    /// The tier of the outermost banded expression, if any.
+    pub fn tier(&self) -> DiroResult<Option<Tier>> {
+        match self {
+            DiroAst::Int(_) | DiroAst::Dice(..) | DiroAst::Draw { .. } => Ok(None),
+            DiroAst::Banded { ast, bands } => Ok(bands.tier(ast.calc()?)),
+            DiroAst::DyadicOP { lhs, rhs, .. } => match lhs.tier()? {
+                Some(t) => Ok(Some(t)),
+                None => rhs.tier(),
+            },
+            DiroAst::Closed(ast) => ast.tier(),
+        }
+    }
+
+    /// The kept face of the first d20 that keeps a single die, e.g. `1d20`,
+    /// `1d20adv` or `2d20k1`.
+    fn natural(&self) -> DiroResult<Option<i32>> {
+        match self {
+            DiroAst::Int(_) => Ok(None),
+            DiroAst::Dice(dice, result) => {
+                let single = match dice {
+                    Dice::Dice {
+                        count: 1, face: 20, ..
+                    } => true,
+                    Dice::Dice { face: 20, kq, .. } => kq.unsigned_abs() == 1,
+                    Dice::Advantage {
+                        count: 1, face: 20, ..
+                    } => true,
+                    _ => false,
+                };
+                if single {
+                    result
+                        .as_ref()
+                        .map(|r| Some(r.result()))
+                        .ok_or(DiroError::DiceNotRolled)
+                } else {
+                    Ok(None)
+                }
+            }
+            DiroAst::DyadicOP { lhs, rhs, .. } => match lhs.natural()? {
+                Some(n) => Ok(Some(n)),
+                None => rhs.natural(),
+            },
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => ast.natural(),
+            DiroAst::Draw { .. } => Ok(None),
+        }
+    }
+
+    /// The expression rolled on a critical hit: every dice is doubled,
+    /// modifiers are not.
+    pub fn critical(&self) -> DiroAst {
+        match self {
+            DiroAst::Int(i) => DiroAst::Int(*i),
+            DiroAst::Dice(dice, _) => DiroAst::Dice(dice.doubled(), None),
+            DiroAst::DyadicOP { verb, lhs, rhs } => DiroAst::DyadicOP {
+                verb: *verb,
+                lhs: Box::new(lhs.critical()),
+                rhs: Box::new(rhs.critical()),
+            },
+            DiroAst::Closed(ast) => DiroAst::Closed(Box::new(ast.critical())),
+            DiroAst::Banded { ast, bands } => ast.critical().banded(bands.clone()),
+            DiroAst::Draw { deck, .. } => DiroAst::Draw {
+                deck: deck.clone(),
+                card: None,
+            },
+        }
+    }
+
+    /// Pushes every rolled Year Zero pool in the expression.
+    pub fn push(&mut self) -> DiroResult<()> {
+        self.push_with(&mut rand::thread_rng())
+    }
+
+    pub fn push_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> DiroResult<()> {
+        let pushed = self.rolled_pools(&mut |dice, result| match dice {
+            Dice::YearZero { .. } => {
+                result
+                    .as_mut()
+                    .ok_or(DiroError::DiceNotRolled)?
+                    .push_with(rng)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        if pushed {
+            Ok(())
+        } else {
+            Err(DiroError::NotPushable)
+        }
+    }
+
+    /// Rerolls the misses of every rolled Shadowrun pool in the expression.
+    pub fn second_chance(&mut self) -> DiroResult<()> {
+        self.second_chance_with(&mut rand::thread_rng())
+    }
+
+    /// Every pool is checked before any is rerolled, so on an error the
+    /// expression is left as it was.
+    pub fn second_chance_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> DiroResult<()> {
+        self.rolled_pools(&mut |dice, result| match dice {
+            Dice::Shadowrun { .. } => {
+                result
+                    .as_ref()
+                    .ok_or(DiroError::DiceNotRolled)?
+                    .check_second_chance()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        let rerolled = self.rolled_pools(&mut |dice, result| match dice {
+            Dice::Shadowrun { .. } => {
+                result
+                    .as_mut()
+                    .ok_or(DiroError::DiceNotRolled)?
+                    .second_chance_with(rng)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        if rerolled {
+            Ok(())
+        } else {
+            Err(DiroError::NoSecondChance)
+        }
+    }
+
+    /// Burns momentum on every rolled Ironsworn action roll in the expression.
+    pub fn burn_momentum(&mut self, momentum: i32) -> DiroResult<()> {
+        let burned = self.rolled_pools(&mut |dice, result| match dice {
+            Dice::Ironsworn {
+                progress: false, ..
+            } => {
+                result
+                    .as_mut()
+                    .ok_or(DiroError::DiceNotRolled)?
+                    .burn_momentum(momentum)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+        if burned {
+            Ok(())
+        } else {
+            Err(DiroError::NotBurnable)
+        }
+    }
+
+    /// Calls `f` on every dice of the expression, returns whether `f`
+    /// returned true for any of them.
+    fn rolled_pools<F>(&mut self, f: &mut F) -> DiroResult<bool>
+    where
+        F: FnMut(&Dice, &mut Option<RollResult>) -> DiroResult<bool>,
+    {
+        match self {
+            DiroAst::Int(_) => Ok(false),
+            DiroAst::Dice(dice, result) => f(dice, result),
+            DiroAst::DyadicOP { lhs, rhs, .. } => {
+                let lhs = lhs.rolled_pools(f)?;
+                Ok(rhs.rolled_pools(f)? || lhs)
+            }
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => ast.rolled_pools(f),
+            DiroAst::Draw { .. } => Ok(false),
+        }
+    }
+
+    /// Draws a card for every `draw(deck)` of the expression. Every deck is
+    /// checked first, so on an error no card is drawn.
+    pub fn draw_decks(&mut self, decks: &mut Decks) -> DiroResult<()> {
+        let mut needed = BTreeMap::<String, usize>::new();
+        self.deck_names(&mut |deck| *needed.entry(deck.to_string()).or_default() += 1);
+        for (name, count) in needed {
+            let deck = decks
+                .get(&name)
+                .ok_or_else(|| DiroError::UnknownDeck(name.clone()))?;
+            if deck.remaining() < count {
+                return Err(DiroError::DeckEmpty(name));
+            }
+        }
+        self.draw_cards(decks)
+    }
+
+    fn deck_names(&self, f: &mut impl FnMut(&str)) {
+        match self {
+            DiroAst::Draw { deck, .. } => f(deck),
+            DiroAst::DyadicOP { lhs, rhs, .. } => {
+                lhs.deck_names(f);
+                rhs.deck_names(f);
+            }
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => ast.deck_names(f),
+            DiroAst::Int(_) | DiroAst::Dice(..) => {}
+        }
+    }
+
+    fn draw_cards(&mut self, decks: &mut Decks) -> DiroResult<()> {
+        match self {
+            DiroAst::Draw { deck, card } => {
+                *card = Some(decks.draw(deck)?);
+                Ok(())
+            }
+            DiroAst::DyadicOP { lhs, rhs, .. } => {
+                lhs.draw_cards(decks)?;
+                rhs.draw_cards(decks)
+            }
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => ast.draw_cards(decks),
+            DiroAst::Int(_) | DiroAst::Dice(..) => Ok(()),
+        }
+    }
+
+    /// Like `eval_with`, but also draws cards from `decks`.
+    pub fn eval_decks<R: Rng + ?Sized>(
+        &mut self,
+        decks: &mut Decks,
+        rng: &mut R,
+    ) -> DiroResult<i32> {
+        self.roll_with(rng);
+        self.draw_decks(decks)?;
+        self.calc()
+    }
+
     pub fn roll(&mut self) {
+        self.roll_with(&mut rand::thread_rng())
+    }
+
+    /// Like `roll`, but rolls with the given RNG.
+    pub fn roll_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         match self {
-            DiroAst::Dice(dice, result) => *result = Some(dice.roll()),
-            DiroAst::Closed(ast) => ast.roll(),
+            DiroAst::Dice(dice, result) => *result = Some(dice.roll_with(rng)),
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => ast.roll_with(rng),
             DiroAst::DyadicOP { lhs, rhs, .. } => {
-                lhs.roll();
-                rhs.roll();
+                lhs.roll_with(rng);
+                rhs.roll_with(rng);
             }
             _ => {}
         }
@@ -69,57 +359,138 @@ impl DiroAst {
                     Err(DiroError::DiceNotRolled)
                 }
             }
-            DiroAst::Closed(ast) => Ok(ast.calc()?),
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => Ok(ast.calc()?),
+            DiroAst::Draw { deck, card } => card
+                .as_ref()
+                .map(|c| c.value)
+                .ok_or_else(|| DiroError::CardNotDrawn(deck.clone())),
         }
     }
 
     pub fn expr(&self) -> String {
-        self.expr_with_priority(1, None).unwrap()
+        self.expr_with_priority(1, None, &Plain).unwrap()
     }
 
     pub fn detail_expr(&self) -> DiroResult<String> {
-        self.expr_with_priority(1, Some(true))
+        self.render(&Plain)
+    }
+
+    /// Renders the rolled expression like `detail_expr` with the given renderer.
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> DiroResult<String> {
+        self.expr_with_priority(1, Some(true), renderer)
     }
 
-    fn expr_with_priority(&self, priority: u8, root: Option<bool>) -> DiroResult<String> {
+    /// Renders `expr = detail = result` with the given renderer.
+    pub fn render_line<R: Renderer + ?Sized>(&self, renderer: &R) -> DiroResult<String> {
+        Ok(renderer.line(
+            &self.expr_with_priority(1, None, renderer)?,
+            &self.render(renderer)?,
+            self.calc()?,
+        ))
+    }
+
+    fn expr_with_priority<R: Renderer + ?Sized>(
+        &self,
+        priority: u8,
+        root: Option<bool>,
+        renderer: &R,
+    ) -> DiroResult<String> {
         match self {
-            DiroAst::Int(i) => Ok(i.to_string()),
+            DiroAst::Int(i) => Ok(renderer.int(*i)),
             DiroAst::DyadicOP { verb, lhs, rhs } => Ok(format!(
                 "{}{}{}",
-                lhs.expr_with_priority(verb.priority(), root.and_then(|_| { Some(false) }))?,
-                verb,
-                rhs.expr_with_priority(verb.priority(), root.and_then(|_| { Some(false) }))?
+                lhs.expr_with_priority(verb.priority(), root.map(|_| false), renderer)?,
+                renderer.verb(*verb),
+                rhs.expr_with_priority(verb.priority(), root.map(|_| false), renderer)?
             )),
             DiroAst::Dice(dice, result) => {
                 if let Some(root) = root {
                     if root {
                         result
                             .as_ref()
-                            .and_then(|r| Some(r.detail()))
-                            .ok_or_else(|| DiroError::DiceNotRolled)
+                            .map(|r| r.render(renderer))
+                            .ok_or(DiroError::DiceNotRolled)
                     } else {
                         result
                             .as_ref()
-                            .and_then(|r| Some(r.result().to_string()))
-                            .ok_or_else(|| DiroError::DiceNotRolled)
+                            .map(|r| renderer.int(r.result()))
+                            .ok_or(DiroError::DiceNotRolled)
                     }
                 } else {
-                    Ok(dice.expr())
+                    Ok(renderer.text(&dice.expr()))
                 }
             }
             DiroAst::Closed(ast) => {
-                if let Self::DyadicOP { verb, .. } = ast.as_ref() {
-                    if verb.priority() < priority {
-                        Ok(format!(
-                            "({})",
-                            ast.expr_with_priority(priority, root.and_then(|_| { Some(false) }))?
-                        ))
+                let inner = ast.expr_with_priority(priority, root.map(|_| false), renderer)?;
+                match ast.as_ref() {
+                    Self::DyadicOP { verb, .. } if verb.priority() < priority => {
+                        Ok(renderer.group(&inner))
+                    }
+                    _ => Ok(inner),
+                }
+            }
+            DiroAst::Draw { deck, card } => match root {
+                None => Ok(renderer.text(&format!("DRAW({})", deck))),
+                Some(root) => {
+                    let card = card
+                        .as_ref()
+                        .ok_or_else(|| DiroError::CardNotDrawn(deck.clone()))?;
+                    if root {
+                        Ok(renderer.die(&card.name, Mark::Normal))
                     } else {
-                        Ok(ast.expr_with_priority(priority, root.and_then(|_| Some(false)))?)
+                        Ok(renderer.int(card.value))
+                    }
+                }
+            },
+            DiroAst::Banded { ast, bands } => match root {
+                None => Ok(format!(
+                    "{}{}",
+                    renderer.text(&bands.name.to_uppercase()),
+                    renderer.group(&ast.expr_with_priority(1, None, renderer)?)
+                )),
+                Some(true) => {
+                    let inner = ast.expr_with_priority(1, Some(true), renderer)?;
+                    match bands.tier(ast.calc()?) {
+                        Some(tier) => Ok(format!(
+                            "{} {}",
+                            inner,
+                            renderer.text(&format!("[{}]", tier.name))
+                        )),
+                        None => Ok(inner),
+                    }
+                }
+                Some(false) => {
+                    let value = ast.calc()?;
+                    match bands.tier(value) {
+                        Some(tier) => Ok(renderer.group(&format!(
+                            "{} {}",
+                            renderer.int(value),
+                            renderer.text(&format!("[{}]", tier.name))
+                        ))),
+                        None => Ok(renderer.int(value)),
                     }
-                } else {
-                    Ok(ast.expr_with_priority(priority, root.and_then(|_| Some(false)))?)
                 }
+            },
+        }
+    }
+
+    /// The smallest and largest result of the expression, computed from the
+    /// extremes of each operand. A `draw(deck)` is only bounded by the card
+    /// it drew, so it needs `draw_decks` first.
+    pub fn bounds(&self) -> DiroResult<(i32, i32)> {
+        match self {
+            DiroAst::Int(i) => Ok((*i, *i)),
+            DiroAst::Dice(dice, _) => Ok(dice.bounds()),
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => ast.bounds(),
+            // a deck is only known once drawn from
+            DiroAst::Draw { deck, card } => card
+                .as_ref()
+                .map(|c| (c.value, c.value))
+                .ok_or_else(|| DiroError::CardNotDrawn(deck.clone())),
+            DiroAst::DyadicOP { verb, lhs, rhs } => {
+                let (l, r) = verb.bounds(lhs.bounds()?, rhs.bounds()?)?;
+                let clamp = |v: i64| v.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+                Ok((clamp(l), clamp(r)))
             }
         }
     }
@@ -132,6 +503,8 @@ impl DiroAst {
             }
             DiroAst::Dice(dice, ..) => dice.expr(),
             DiroAst::Closed(ast) => ast.s_expr(),
+            DiroAst::Banded { ast, bands } => format!("({} {})", bands.name, ast.s_expr()),
+            DiroAst::Draw { deck, .. } => format!("(draw {})", deck),
         }
     }
 
@@ -174,6 +547,64 @@ impl Verb {
         }
     }
 
+    fn apply(&self, lhs: i64, rhs: i64) -> DiroResult<i64> {
+        match self {
+            Verb::Plus => Ok(lhs.saturating_add(rhs)),
+            Verb::Minus => Ok(lhs.saturating_sub(rhs)),
+            Verb::Times => Ok(lhs.saturating_mul(rhs)),
+            Verb::Divide | Verb::Modulo if rhs == 0 => Err(DiroError::ZeroDivision),
+            Verb::Divide => Ok(lhs / rhs),
+            Verb::Modulo => Ok(lhs % rhs),
+            Verb::Power => Ok(lhs.saturating_pow(rhs.clamp(0, u32::MAX as i64) as u32)),
+        }
+    }
+
+    /// The range of `lhs verb rhs` over the ranges of both operands.
+    fn bounds(&self, lhs: (i32, i32), rhs: (i32, i32)) -> DiroResult<(i64, i64)> {
+        let (l, r) = ((lhs.0 as i64, lhs.1 as i64), (rhs.0 as i64, rhs.1 as i64));
+        // the divisors, split around zero
+        let divisors = [(r.0, r.1.min(-1)), (r.0.max(1), r.1)]
+            .into_iter()
+            .filter(|(low, high)| low <= high)
+            .collect::<Vec<_>>();
+        match self {
+            Verb::Divide | Verb::Modulo if divisors.is_empty() => Err(DiroError::ZeroDivision),
+            // the remainder takes the sign of `lhs` and is smaller than the divisor
+            Verb::Modulo => {
+                let largest = r.0.abs().max(r.1.abs()) - 1;
+                let smallest = divisors
+                    .iter()
+                    .map(|(low, high)| if *low > 0 { *low } else { -*high })
+                    .min()
+                    .unwrap();
+                if l.0.abs().max(l.1.abs()) < smallest {
+                    Ok(l)
+                } else {
+                    Ok((l.0.max(-largest).min(0), l.1.min(largest).max(0)))
+                }
+            }
+            // a negative base alternates in sign
+            Verb::Power if l.0 < 0 => Err(DiroError::Unbounded(self.expr())),
+            // the rest are monotone in each operand, as long as the divisor
+            // keeps its sign
+            _ => {
+                let mut values = vec![];
+                for (low, high) in if *self == Verb::Divide {
+                    divisors
+                } else {
+                    vec![r]
+                } {
+                    for a in [l.0, l.1] {
+                        for b in [low, high] {
+                            values.push(self.apply(a, b)?);
+                        }
+                    }
+                }
+                Ok((*values.iter().min().unwrap(), *values.iter().max().unwrap()))
+            }
+        }
+    }
+
     fn priority(&self) -> u8 {
         match self {
             Verb::Plus | Verb::Minus => 1,
@@ -1,7 +1,13 @@
 use pest::{iterators::Pair, Parser};
 use pest_derive::Parser;
 
-use crate::{error::DiroResult, Dice};
+use crate::{
+    bands::Bands,
+    error::{DiroError, DiroResult},
+    fitd::FitdKind,
+    narrative::NarrativeDie,
+    Dice,
+};
 
 mod ast;
 pub use ast::*;
@@ -13,11 +19,8 @@ struct DiroParser;
 pub fn parse(source: &str) -> DiroResult<DiroAst> {
     let pairs = DiroParser::parse(Rule::main, source)?;
     for pair in pairs {
-        match pair.as_rule() {
-            Rule::expr => {
-                return parse_expr(pair);
-            }
-            _ => {}
+        if pair.as_rule() == Rule::expr {
+            return parse_expr(pair);
         }
     }
     Ok(DiroAst::Dice(crate::Dice::default(), None))
@@ -53,6 +56,18 @@ fn parse_term(pair: Pair<Rule>) -> DiroResult<DiroAst> {
         Rule::adice => parse_adice(pair),
         Rule::cdice => parse_cdice(pair),
         Rule::fdice => parse_fdice(pair),
+        Rule::rollkeep => parse_rollkeep(pair),
+        Rule::narrative => parse_narrative(pair),
+        Rule::yzdice => parse_yzdice(pair),
+        Rule::fitd => parse_fitd(pair),
+        Rule::gurps => parse_gurps(pair),
+        Rule::v5 => parse_v5(pair),
+        Rule::ironsworn => parse_ironsworn(pair),
+        Rule::savage => parse_savage(pair),
+        Rule::shadowrun => parse_shadowrun(pair),
+        Rule::cortex => parse_cortex(pair),
+        Rule::banded => parse_banded(pair),
+        Rule::draw => parse_draw(pair),
         Rule::expr => parse_expr(pair).map(|a| DiroAst::Closed(Box::new(a))),
         Rule::int => Ok(DiroAst::Int(pair.as_str().parse()?)),
         _ => unreachable!(),
@@ -78,9 +93,12 @@ fn parse_dice(pair: Pair<Rule>) -> DiroResult<DiroAst> {
     let mut count = 1;
     let mut face = 100;
     let mut a = 0;
+    let mut adv = None;
     for pair in pairs {
         match pair.as_rule() {
             Rule::base_dice => parse_base_dice(pair, &mut count, &mut face)?,
+            Rule::adv => adv = Some(true),
+            Rule::dis => adv = Some(false),
             Rule::b => parse_bp(pair, &mut bp, true)?,
             Rule::p => parse_bp(pair, &mut bp, false)?,
             Rule::k => parse_bp(pair, &mut kq, true)?,
@@ -89,6 +107,15 @@ fn parse_dice(pair: Pair<Rule>) -> DiroResult<DiroAst> {
             _ => unreachable!(),
         }
     }
+    if let Some(advantage) = adv {
+        if bp != 0 || kq != 0 || a != 0 {
+            return Err(DiroError::AdvantageConflict);
+        }
+        return Ok(DiroAst::Dice(
+            crate::Dice::advantage(count, face, advantage)?,
+            None,
+        ));
+    }
     Ok(DiroAst::Dice(
         crate::Dice::_dice(count, face, bp, kq, a)?,
         None,
@@ -204,13 +231,178 @@ fn parse_cdice(pair: Pair<Rule>) -> DiroResult<DiroAst> {
 fn parse_fdice(pair: Pair<Rule>) -> DiroResult<DiroAst> {
     let pairs = pair.into_inner();
     let mut count = 1;
+    let mut variant = "2";
     for pair in pairs {
         match pair.as_rule() {
             Rule::uint => count = pair.as_str().parse()?,
+            Rule::d => {}
+            Rule::fvariant => variant = &pair.as_str()[1..],
             _ => unreachable!(),
         }
     }
-    Ok(DiroAst::Dice(Dice::FDice(count), None))
+    if count == 0 {
+        return Err(DiroError::NoDice);
+    }
+    if variant == "1" {
+        Ok(DiroAst::Dice(Dice::FDice1(count), None))
+    } else {
+        Ok(DiroAst::Dice(Dice::FDice(count), None))
+    }
+}
+
+fn parse_narrative(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut pool = vec![];
+    let mut count = 1;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::uint => count = pair.as_str().parse()?,
+            Rule::narrative_die => {
+                let c = pair.as_str().chars().next().unwrap();
+                pool.push((count, NarrativeDie::from_letter(c).unwrap()));
+            }
+            _ => unreachable!(),
+        }
+    }
+    if pool.iter().all(|(count, _)| *count == 0) {
+        return Err(DiroError::NoDice);
+    }
+    Ok(DiroAst::Dice(Dice::Narrative(pool), None))
+}
+
+fn parse_yzdice(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut base: u8 = 0;
+    let mut skill = 0;
+    let mut gear = 0;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::uint => base = pair.as_str().parse()?,
+            Rule::yz_skill => skill = pair.into_inner().as_str().parse()?,
+            Rule::yz_gear => gear = pair.into_inner().as_str().parse()?,
+            _ => unreachable!(),
+        }
+    }
+    if base == 0 && skill == 0 && gear == 0 {
+        return Err(DiroError::NoDice);
+    }
+    Ok(DiroAst::Dice(Dice::YearZero { base, skill, gear }, None))
+}
+
+fn parse_fitd(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut count = 0;
+    let mut kind = FitdKind::Action;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::uint => count = pair.as_str().parse()?,
+            Rule::fitd_kind => {
+                if pair.as_str().eq_ignore_ascii_case("r") {
+                    kind = FitdKind::Resistance
+                } else {
+                    kind = FitdKind::Fortune
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(DiroAst::Dice(Dice::Fitd { count, kind }, None))
+}
+
+fn parse_rollkeep(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut inner = pair.into_inner();
+    let rolled = inner.next().unwrap().as_str().parse()?;
+    let kept = inner.next().unwrap().as_str().parse()?;
+    Ok(DiroAst::Dice(Dice::roll_keep(rolled, kept)?, None))
+}
+
+fn parse_shadowrun(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut count = 0;
+    let mut edge = false;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::uint => count = pair.as_str().parse()?,
+            Rule::sr_edge => edge = true,
+            _ => unreachable!(),
+        }
+    }
+    Ok(DiroAst::Dice(Dice::shadowrun(count, edge)?, None))
+}
+
+fn parse_gurps(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let skill = pair.into_inner().as_str().parse()?;
+    Ok(DiroAst::Dice(Dice::Gurps(skill), None))
+}
+
+fn parse_v5(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let (mut pool, mut hunger, mut difficulty) = (0, 0, None);
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::v5_pool => {
+                for pair in pair.into_inner() {
+                    match pair.as_rule() {
+                        Rule::uint => pool = pair.as_str().parse()?,
+                        Rule::v5_hunger => hunger = pair.into_inner().as_str().parse()?,
+                        _ => unreachable!(),
+                    }
+                }
+            }
+            Rule::v5_diff => difficulty = Some(pair.into_inner().as_str().parse()?),
+            _ => unreachable!(),
+        }
+    }
+    Ok(DiroAst::Dice(Dice::v5(pool, hunger, difficulty)?, None))
+}
+
+fn parse_ironsworn(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut score: u8 = 0;
+    let mut progress = false;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::is_progress => progress = true,
+            Rule::uint => score = pair.as_str().parse()?,
+            Rule::is_add => {
+                let add: u8 = pair.into_inner().as_str().parse()?;
+                score = score.saturating_add(add);
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(DiroAst::Dice(Dice::Ironsworn { score, progress }, None))
+}
+
+fn parse_cortex(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut pool = vec![];
+    for pair in pair.into_inner() {
+        let (mut count, mut face) = (1, 0);
+        parse_base_dice(pair, &mut count, &mut face)?;
+        pool.push((count, face));
+    }
+    Ok(DiroAst::Dice(Dice::cortex(pool)?, None))
+}
+
+fn parse_savage(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut face = 0;
+    let mut modifier = 0;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::d => {}
+            Rule::uint => face = pair.as_str().parse()?,
+            Rule::savage_mod => modifier = pair.as_str().parse()?,
+            _ => unreachable!(),
+        }
+    }
+    Ok(DiroAst::Dice(Dice::savage(face, modifier)?, None))
+}
+
+fn parse_draw(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    Ok(DiroAst::Draw {
+        deck: pair.into_inner().as_str().to_string(),
+        card: None,
+    })
+}
+
+fn parse_banded(pair: Pair<Rule>) -> DiroResult<DiroAst> {
+    let mut inner = pair.into_inner();
+    let bands = Bands::preset(inner.next().unwrap().as_str()).unwrap();
+    Ok(parse_expr(inner.next().unwrap())?.banded(bands))
 }
 
 #[test]
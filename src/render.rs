@@ -0,0 +1,218 @@
+use crate::parse::Verb;
+
+/// How a single die face should be highlighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Normal,
+    /// Rolled but not counted, e.g. dropped by `k`/`q`.
+    Dropped,
+    Success,
+    Failure,
+    Critical,
+}
+
+/// Turns an evaluated `DiroAst` or `RollResult` into text.
+///
+/// Every method has a plain-text default except `die`, so a custom renderer
+/// only needs to override the pieces it wants to decorate.
+pub trait Renderer {
+    /// Renders a single die face.
+    fn die(&self, face: &str, mark: Mark) -> String;
+
+    /// Renders literal text such as separators and dice expressions.
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+
+    fn int(&self, i: i32) -> String {
+        self.text(&i.to_string())
+    }
+
+    fn verb(&self, verb: Verb) -> String {
+        self.text(&verb.expr())
+    }
+
+    /// Renders a parenthesized sub expression.
+    fn group(&self, inner: &str) -> String {
+        format!("{}{}{}", self.text("("), inner, self.text(")"))
+    }
+
+    /// Renders the final result of an expression.
+    fn total(&self, total: i32) -> String {
+        self.int(total)
+    }
+
+    /// Renders a whole `expr = detail = result` line.
+    fn line(&self, expr: &str, detail: &str, total: i32) -> String {
+        format!(
+            "{}{}{}{}{}",
+            expr,
+            self.text(" = "),
+            detail,
+            self.text(" = "),
+            self.total(total)
+        )
+    }
+}
+
+/// Plain text, the format of `detail_expr`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Plain;
+
+impl Renderer for Plain {
+    fn die(&self, face: &str, _mark: Mark) -> String {
+        face.to_string()
+    }
+}
+
+/// Markdown with bold totals and ~~struck~~ dropped dice.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn die(&self, face: &str, mark: Mark) -> String {
+        match mark {
+            Mark::Dropped => format!("~~{}~~", self.text(face)),
+            Mark::Critical => format!("**{}**", self.text(face)),
+            _ => self.text(face),
+        }
+    }
+
+    fn text(&self, text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(c, '\\' | '*' | '_' | '~' | '`' | '[' | ']' | '#' | '|') {
+                s.push('\\');
+            }
+            s.push(c);
+        }
+        s
+    }
+
+    fn total(&self, total: i32) -> String {
+        format!("**{}**", total)
+    }
+}
+
+/// HTML `<span>`s with `diro-*` CSS classes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Html;
+
+impl Renderer for Html {
+    fn die(&self, face: &str, mark: Mark) -> String {
+        let class = match mark {
+            Mark::Normal => "diro-die",
+            Mark::Dropped => "diro-die diro-dropped",
+            Mark::Success => "diro-die diro-success",
+            Mark::Failure => "diro-die diro-failure",
+            Mark::Critical => "diro-die diro-critical",
+        };
+        format!("<span class=\"{}\">{}</span>", class, self.text(face))
+    }
+
+    fn text(&self, text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' => s.push_str("&amp;"),
+                '<' => s.push_str("&lt;"),
+                '>' => s.push_str("&gt;"),
+                '"' => s.push_str("&quot;"),
+                '\'' => s.push_str("&#39;"),
+                _ => s.push(c),
+            }
+        }
+        s
+    }
+
+    fn total(&self, total: i32) -> String {
+        format!("<span class=\"diro-total\">{}</span>", total)
+    }
+
+    fn line(&self, expr: &str, detail: &str, total: i32) -> String {
+        format!(
+            "<span class=\"diro-roll\"><span class=\"diro-expr\">{}</span> = <span class=\"diro-detail\">{}</span> = {}</span>",
+            expr,
+            detail,
+            self.total(total)
+        )
+    }
+}
+
+/// ANSI escape codes for terminals.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ansi;
+
+impl Renderer for Ansi {
+    fn die(&self, face: &str, mark: Mark) -> String {
+        match mark {
+            Mark::Normal => face.to_string(),
+            Mark::Dropped => format!("\x1b[2;9m{}\x1b[0m", face),
+            Mark::Success => format!("\x1b[32m{}\x1b[0m", face),
+            Mark::Failure => format!("\x1b[31m{}\x1b[0m", face),
+            Mark::Critical => format!("\x1b[1;33m{}\x1b[0m", face),
+        }
+    }
+
+    fn total(&self, total: i32) -> String {
+        format!("\x1b[1m{}\x1b[0m", total)
+    }
+}
+
+/// BBCode for forums.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BBCode;
+
+impl Renderer for BBCode {
+    fn die(&self, face: &str, mark: Mark) -> String {
+        match mark {
+            Mark::Normal => face.to_string(),
+            Mark::Dropped => format!("[s]{}[/s]", face),
+            Mark::Success => format!("[color=green]{}[/color]", face),
+            Mark::Failure => format!("[color=red]{}[/color]", face),
+            Mark::Critical => format!("[b][color=orange]{}[/color][/b]", face),
+        }
+    }
+
+    fn total(&self, total: i32) -> String {
+        format!("[b]{}[/b]", total)
+    }
+}
+
+#[test]
+fn render_test() {
+    use crate::{DiroAst, RollResult};
+
+    let ast = DiroAst::Dice(
+        crate::Dice::dice(4, 6, 3).unwrap(),
+        Some(RollResult::Dice {
+            kq: 3,
+            result: vec![2, 5, 1, 6],
+        }),
+    );
+    assert_eq!(ast.detail_expr().unwrap(), "2+5+1+6");
+    assert_eq!(ast.render_line(&Plain).unwrap(), "4D6K3 = 2+5+1+6 = 13");
+    assert_eq!(
+        ast.render_line(&Markdown).unwrap(),
+        "4D6K3 = 2+5+~~1~~+6 = **13**"
+    );
+    assert_eq!(ast.render(&BBCode).unwrap(), "2+5+[s]1[/s]+6");
+    assert_eq!(
+        ast.render(&Html).unwrap(),
+        "<span class=\"diro-die\">2</span>+<span class=\"diro-die\">5</span>+<span class=\"diro-die diro-dropped\">1</span>+<span class=\"diro-die\">6</span>"
+    );
+
+    let ast = DiroAst::DyadicOP {
+        verb: crate::Verb::Times,
+        lhs: Box::new(ast),
+        rhs: Box::new(DiroAst::Int(2)),
+    };
+    assert_eq!(
+        ast.render_line(&Markdown).unwrap(),
+        "4D6K3\\*2 = 13\\*2 = **26**"
+    );
+    assert_eq!(
+        ast.render_line(&Ansi).unwrap(),
+        "4D6K3*2 = 13*2 = \x1b[1m26\x1b[0m"
+    );
+}
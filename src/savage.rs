@@ -0,0 +1,60 @@
+//! Savage Worlds trait rolls with a wild die.
+
+/// Every trait roll is made against 4, each 4 over it is a raise.
+pub const TARGET: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SavageOutcome {
+    /// Snake eyes, a 1 on both the trait and the wild die.
+    CriticalFailure,
+    Failure,
+    Success {
+        raises: i32,
+    },
+}
+
+impl SavageOutcome {
+    pub fn new(total: i32, snake_eyes: bool) -> Self {
+        if snake_eyes {
+            Self::CriticalFailure
+        } else if total < TARGET {
+            Self::Failure
+        } else {
+            Self::Success {
+                raises: (total - TARGET) / 4,
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::CriticalFailure => "critical failure".to_string(),
+            Self::Failure => "failure".to_string(),
+            Self::Success { raises: 0 } => "success".to_string(),
+            Self::Success { raises: 1 } => "success, 1 raise".to_string(),
+            Self::Success { raises } => format!("success, {} raises", raises),
+        }
+    }
+}
+
+#[test]
+fn savage_test() {
+    assert_eq!(SavageOutcome::new(3, false), SavageOutcome::Failure);
+    assert_eq!(
+        SavageOutcome::new(4, false),
+        SavageOutcome::Success { raises: 0 }
+    );
+    assert_eq!(
+        SavageOutcome::new(8, false),
+        SavageOutcome::Success { raises: 1 }
+    );
+    assert_eq!(
+        SavageOutcome::new(15, false),
+        SavageOutcome::Success { raises: 2 }
+    );
+    assert_eq!(SavageOutcome::new(2, true), SavageOutcome::CriticalFailure);
+    assert_eq!(
+        SavageOutcome::Success { raises: 2 }.describe(),
+        "success, 2 raises"
+    );
+}
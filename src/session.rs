@@ -0,0 +1,261 @@
+//! An auditable log of every roll of a session, replayable from its seed.
+//!
+//! Only the roll itself is logged: `push`, `second_chance` and
+//! `burn_momentum` made afterwards are not, and `draw(deck)` expressions
+//! fail to evaluate without decks, so they can't be logged either.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::error::{DiroError, DiroResult};
+use crate::parse::{parse, DiroAst};
+
+/// The RNG every roll is made with, recorded in `LogEntry::rng`. Unlike
+/// `StdRng` its output is fixed across rand versions and platforms.
+pub const RNG: &str = "chacha20";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogEntry {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub user: String,
+    /// The source expression as typed.
+    pub expr: String,
+    /// The RNG the expression was rolled with, see `RNG`.
+    pub rng: String,
+    /// The seed of that RNG.
+    pub seed: u64,
+    /// The rolled expression, holding every `RollResult`.
+    pub ast: DiroAst,
+    pub total: i32,
+}
+
+/// Roll statistics of one user.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserStats {
+    pub rolls: usize,
+    /// Rolls with a natural d20, see `Outcome::natural`.
+    pub d20_rolls: usize,
+    pub average_d20: Option<f64>,
+    pub criticals: usize,
+    pub fumbles: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    rng: ChaCha20Rng,
+    entries: Vec<LogEntry>,
+}
+
+impl LogEntry {
+    /// e.g. `1D20+5 = 12+5 = 17`.
+    pub fn detail(&self) -> DiroResult<String> {
+        self.ast.render_line(&crate::Plain)
+    }
+
+    /// Parses and rolls the source expression again from the stored seed.
+    pub fn replay(&self) -> DiroResult<DiroAst> {
+        if self.rng != RNG {
+            return Err(DiroError::UnknownRng(self.rng.clone()));
+        }
+        let mut ast = parse(&self.expr)?;
+        ast.eval_with(&mut ChaCha20Rng::seed_from_u64(self.seed))?;
+        Ok(ast)
+    }
+
+    /// Whether the replay gives the same dice and total as logged.
+    pub fn verify(&self) -> DiroResult<bool> {
+        let ast = self.replay()?;
+        Ok(ast == self.ast && ast.calc()? == self.total)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session::with_seed(rand::thread_rng().gen())
+    }
+
+    /// A session whose roll seeds are drawn from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Session {
+            rng: ChaCha20Rng::seed_from_u64(seed),
+            entries: vec![],
+        }
+    }
+
+    /// Rolls `source` for `user` and logs it, expressions that fail to
+    /// parse or evaluate are not logged.
+    pub fn roll(&mut self, user: &str, source: &str) -> DiroResult<&LogEntry> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        self.roll_at(user, source, timestamp)
+    }
+
+    /// Like `roll`, but logs the given timestamp.
+    pub fn roll_at(&mut self, user: &str, source: &str, timestamp: u64) -> DiroResult<&LogEntry> {
+        let seed = self.rng.gen();
+        let mut ast = parse(source)?;
+        let total = ast.eval_with(&mut ChaCha20Rng::seed_from_u64(seed))?;
+        self.entries.push(LogEntry {
+            timestamp,
+            user: user.to_string(),
+            expr: source.to_string(),
+            rng: RNG.to_string(),
+            seed,
+            ast,
+            total,
+        });
+        Ok(self.entries.last().unwrap())
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    /// The log as CSV with a header line, one row per roll.
+    pub fn to_csv(&self) -> DiroResult<String> {
+        let mut csv = String::from("timestamp,user,expr,seed,detail,total\n");
+        for entry in &self.entries {
+            let row = [
+                entry.timestamp.to_string(),
+                csv_field(&entry.user),
+                csv_field(&entry.expr),
+                entry.seed.to_string(),
+                csv_field(&entry.detail()?),
+                entry.total.to_string(),
+            ];
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
+    /// The log as JSON Lines, one `LogEntry` per line.
+    #[cfg(feature = "serde")]
+    pub fn to_json_lines(&self) -> DiroResult<String> {
+        let mut lines = String::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| crate::DiroError::InvalidLog(i + 1, e.to_string()))?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+
+    /// Restores a log written by `to_json_lines`, new rolls get new seeds.
+    #[cfg(feature = "serde")]
+    pub fn from_json_lines(source: &str) -> DiroResult<Self> {
+        let mut session = Session::new();
+        for (i, line) in source.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(line)
+                .map_err(|e| crate::DiroError::InvalidLog(i + 1, e.to_string()))?;
+            session.entries.push(entry);
+        }
+        Ok(session)
+    }
+
+    /// Statistics of every user who rolled, by name.
+    pub fn stats(&self) -> BTreeMap<String, UserStats> {
+        let mut naturals = BTreeMap::<String, (UserStats, i64)>::new();
+        for entry in &self.entries {
+            let (stats, sum) = naturals.entry(entry.user.clone()).or_default();
+            stats.rolls += 1;
+            let Ok(outcome) = entry.ast.outcome() else {
+                continue;
+            };
+            if let Some(natural) = outcome.natural {
+                stats.d20_rolls += 1;
+                *sum += natural as i64;
+            }
+            stats.criticals += outcome.critical as usize;
+            stats.fumbles += outcome.fumble as usize;
+        }
+        naturals
+            .into_iter()
+            .map(|(user, (mut stats, sum))| {
+                if stats.d20_rolls > 0 {
+                    stats.average_d20 = Some(sum as f64 / stats.d20_rolls as f64);
+                }
+                (user, stats)
+            })
+            .collect()
+    }
+}
+
+/// Quotes a field holding a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[test]
+fn session_test() {
+    let mut session = Session::with_seed(7);
+    for i in 0..20 {
+        session.roll_at("alice", "1d20+5", i).unwrap();
+    }
+    session.roll_at("bob", "{d8,d6}", 20).unwrap();
+    assert!(session.roll_at("bob", "1d20+", 21).is_err());
+    assert_eq!(session.entries().len(), 21);
+
+    let entry = &session.entries()[3];
+    assert!(entry.verify().unwrap());
+    let mut forged = entry.clone();
+    forged.total += 1;
+    assert!(!forged.verify().unwrap());
+    let mut other = entry.clone();
+    other.rng = "stdrng".to_string();
+    assert!(matches!(other.verify(), Err(DiroError::UnknownRng(_))));
+    // the same seed always replays the same dice
+    assert_eq!(
+        Session::with_seed(7)
+            .roll_at("alice", "1d20+5", 0)
+            .unwrap()
+            .ast,
+        session.entries()[0].ast
+    );
+    assert!(session.roll_at("bob", "draw(init)", 22).is_err());
+
+    let csv = session.to_csv().unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("timestamp,user,expr,seed,detail,total"));
+    assert!(lines.nth(20).unwrap().starts_with("20,bob,\"{d8,d6}\","));
+
+    let stats = session.stats();
+    let alice = &stats["alice"];
+    let naturals = session.entries()[..20]
+        .iter()
+        .map(|e| e.total - 5)
+        .collect::<Vec<_>>();
+    assert_eq!((alice.rolls, alice.d20_rolls), (20, 20));
+    assert_eq!(
+        alice.average_d20,
+        Some(naturals.iter().sum::<i32>() as f64 / 20.0)
+    );
+    assert_eq!(
+        alice.criticals,
+        naturals.iter().filter(|n| **n == 20).count()
+    );
+    assert_eq!(alice.fumbles, naturals.iter().filter(|n| **n == 1).count());
+    assert_eq!((stats["bob"].rolls, stats["bob"].average_d20), (1, None));
+
+    assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+}
@@ -0,0 +1,132 @@
+//! Shadowrun d6 pools counting hits on 5 and 6.
+
+use rand::Rng;
+
+/// One throw of a Shadowrun pool, the first of `RollResult::Shadowrun` is the
+/// original roll and the second the Second Chance reroll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShadowrunRoll {
+    /// The dice in the pool, sixes added by Push the Limit are not counted.
+    pub pool: u8,
+    /// Pushed the Limit, so sixes explode and no Second Chance is allowed.
+    pub edge: bool,
+    /// The original dice followed by every round of exploded sixes.
+    pub rounds: Vec<Vec<i32>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShadowrunOutcome {
+    pub hits: i32,
+    /// More than half the pool shows 1s, exploded dice are not counted.
+    pub glitch: bool,
+    /// A glitch without any hit.
+    pub critical_glitch: bool,
+}
+
+pub fn is_hit(die: i32) -> bool {
+    die >= 5
+}
+
+impl ShadowrunRoll {
+    /// Rerolls every die that is not a hit.
+    pub(crate) fn second_chance<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        ShadowrunRoll {
+            pool: self.pool,
+            edge: self.edge,
+            rounds: self
+                .rounds
+                .iter()
+                .map(|round| {
+                    round
+                        .iter()
+                        .map(|d| if is_hit(*d) { *d } else { rng.gen_range(1..=6) })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn hits(&self) -> i32 {
+        self.rounds.iter().flatten().filter(|d| is_hit(**d)).count() as i32
+    }
+
+    /// The 1s among the original dice, as a glitch is measured against
+    /// the pool without the dice added by exploded sixes.
+    pub fn ones(&self) -> i32 {
+        self.rounds[0].iter().filter(|d| **d == 1).count() as i32
+    }
+
+    pub fn outcome(&self) -> ShadowrunOutcome {
+        let hits = self.hits();
+        let glitch = self.ones() * 2 > self.pool as i32;
+        ShadowrunOutcome {
+            hits,
+            glitch,
+            critical_glitch: glitch && hits == 0,
+        }
+    }
+}
+
+impl ShadowrunOutcome {
+    pub fn describe(&self) -> String {
+        let hits = match self.hits {
+            1 => "1 hit".to_string(),
+            n => format!("{} hits", n),
+        };
+        if self.critical_glitch {
+            "critical glitch".to_string()
+        } else if self.glitch {
+            format!("{}, glitch", hits)
+        } else {
+            hits
+        }
+    }
+}
+
+#[test]
+fn shadowrun_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let roll = |rounds: Vec<Vec<i32>>| ShadowrunRoll {
+        pool: rounds[0].len() as u8,
+        edge: rounds.len() > 1,
+        rounds,
+    };
+    let r = roll(vec![vec![6, 5, 1, 3], vec![6], vec![2]]);
+    assert_eq!(
+        r.outcome(),
+        ShadowrunOutcome {
+            hits: 3,
+            ..Default::default()
+        }
+    );
+    assert_eq!(r.outcome().describe(), "3 hits");
+
+    let r = roll(vec![vec![1, 1, 5, 3]]);
+    assert_eq!(
+        r.outcome(),
+        ShadowrunOutcome {
+            hits: 1,
+            ..Default::default()
+        }
+    );
+
+    let r = roll(vec![vec![1, 1, 5, 1]]);
+    assert!(r.outcome().glitch && !r.outcome().critical_glitch);
+    assert_eq!(r.outcome().describe(), "1 hit, glitch");
+
+    // the 1s rolled by exploded sixes don't glitch
+    let r = roll(vec![vec![6, 6, 1, 3], vec![1, 1]]);
+    assert_eq!(r.ones(), 1);
+    assert!(!r.outcome().glitch);
+
+    let r = roll(vec![vec![1, 1, 2]]);
+    assert!(r.outcome().critical_glitch);
+    assert_eq!(r.outcome().describe(), "critical glitch");
+
+    let r = roll(vec![vec![6, 1, 5, 2]]);
+    let second = r.second_chance(&mut StdRng::seed_from_u64(1));
+    assert_eq!((second.rounds[0][0], second.rounds[0][2]), (6, 5));
+    assert!(second.hits() >= 2);
+}
@@ -0,0 +1,612 @@
+//! Random tables rolled with a dice expression.
+//!
+//! The text format has one `[name]` header per table followed by its
+//! entries. `#` starts a comment at the start of a line, or after a space
+//! when followed by a space or the line end, so `Room #3` is kept:
+//!
+//! ```text
+//! [encounter]
+//! dice: 1d6
+//! 1-3: goblin
+//! 4: 2d4 wolves
+//! 5-6: {loot}
+//!
+//! [loot]
+//! [2d6*10] gold
+//! a rusty sword
+//! ```
+//!
+//! An entry without a range takes the next number, and a table without a
+//! `dice` line rolls `1d` its highest number. Words with dice such as
+//! `2d4` and expressions in brackets such as `[2d6*10]` are rolled, and
+//! `{loot}` rolls on the `loot` table. A header is a name without brackets
+//! or braces; a lone `[1d6]` is a dice expression and read as an entry.
+//! Tables referring to each other in a cycle are rejected.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::{
+    error::{DiroError, DiroResult},
+    parse, DiroAst, Plain, Renderer,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub range: RangeInclusive<i32>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub name: String,
+    pub dice: DiroAst,
+    pub entries: Vec<Entry>,
+}
+
+/// A set of tables that may refer to each other.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tables {
+    tables: BTreeMap<String, Table>,
+}
+
+/// One roll on a table and every roll made while expanding its entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRoll {
+    pub table: String,
+    /// The rolled table dice.
+    pub roll: DiroAst,
+    /// The entry with every dice and reference expanded.
+    pub text: String,
+    pub steps: Vec<TableStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableStep {
+    Dice(DiroAst),
+    Table(TableRoll),
+}
+
+impl Table {
+    /// Without `dice` the table rolls `1d` its highest number. Overlapping
+    /// ranges are an error.
+    pub fn new<S: Into<String>>(
+        name: S,
+        dice: Option<DiroAst>,
+        entries: Vec<Entry>,
+    ) -> DiroResult<Self> {
+        let name = name.into();
+        let mut entries = entries;
+        entries.sort_by_key(|e| *e.range.start());
+        for pair in entries.windows(2) {
+            if pair[1].range.start() <= pair[0].range.end() {
+                return Err(DiroError::InvalidTable(format!(
+                    "[{}] {} overlaps {}-{}",
+                    name,
+                    pair[1].text,
+                    pair[0].range.start(),
+                    pair[0].range.end()
+                )));
+            }
+        }
+        let dice = match dice {
+            Some(dice) => dice,
+            None => {
+                let max = entries.last().map_or(0, |e| *e.range.end());
+                if max < 1 {
+                    return Err(DiroError::InvalidTable(format!("[{}] has no entry", name)));
+                }
+                parse(&format!("1d{}", max))?
+            }
+        };
+        Ok(Table {
+            name,
+            dice,
+            entries,
+        })
+    }
+
+    pub fn entry(&self, roll: i32) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.range.contains(&roll))
+    }
+}
+
+impl Tables {
+    /// Loads tables from the text format described in the module docs.
+    pub fn parse(source: &str) -> DiroResult<Self> {
+        fn finish(
+            tables: &mut Tables,
+            current: Option<(String, Option<DiroAst>, Vec<Entry>)>,
+        ) -> DiroResult<()> {
+            if let Some((name, dice, entries)) = current {
+                tables.insert(Table::new(name, dice, entries)?)?;
+            }
+            Ok(())
+        }
+
+        let mut tables = Tables::default();
+        let mut current: Option<(String, Option<DiroAst>, Vec<Entry>)> = None;
+        for (number, line) in source.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid =
+                |reason: &str| DiroError::InvalidTable(format!("line {}: {}", number + 1, reason));
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                if name.is_empty() || name.contains(['{', '}']) {
+                    return Err(invalid(&format!("[{}] is not a table name", name)));
+                }
+                if !name.contains(['[', ']']) && parse(name).is_err() {
+                    finish(&mut tables, current.take())?;
+                    current = Some((name.to_string(), None, vec![]));
+                    continue;
+                }
+            }
+            let (_, dice, entries) = current
+                .as_mut()
+                .ok_or_else(|| invalid("entry before any [table]"))?;
+            let (key, text) = match line.split_once([':', '：']) {
+                Some((key, text)) => (key.trim(), text.trim()),
+                None => ("", line),
+            };
+            if key.eq_ignore_ascii_case("dice") {
+                *dice = Some(parse(text)?);
+            } else if let Some(range) = parse_range(key) {
+                entries.push(Entry {
+                    range,
+                    text: text.to_string(),
+                });
+            } else {
+                let next = entries.last().map_or(1, |e| e.range.end() + 1);
+                entries.push(Entry {
+                    range: next..=next,
+                    text: line.to_string(),
+                });
+            }
+        }
+        finish(&mut tables, current)?;
+        Ok(tables)
+    }
+
+    /// Loads tables from TOML, one TOML table per table with a `dice` key
+    /// and one key per range:
+    ///
+    /// ```toml
+    /// [encounter]
+    /// dice = "1d6"
+    /// "1-3" = "goblin"
+    /// 4 = "2d4 wolves"
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(source: &str) -> DiroResult<Self> {
+        let invalid = |reason: String| DiroError::InvalidTable(reason);
+        let root: toml::Table = source.parse().map_err(|e| invalid(format!("{}", e)))?;
+        let mut tables = Tables::default();
+        for (name, value) in root {
+            let table = value
+                .as_table()
+                .ok_or_else(|| invalid(format!("{} is not a table", name)))?;
+            let mut dice = None;
+            let mut entries = vec![];
+            for (key, value) in table {
+                let text = value
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("[{}] {} is not a string", name, key)))?;
+                if key.eq_ignore_ascii_case("dice") {
+                    dice = Some(parse(text)?);
+                } else {
+                    let range = parse_range(key)
+                        .ok_or_else(|| invalid(format!("[{}] {} is not a range", name, key)))?;
+                    entries.push(Entry {
+                        range,
+                        text: text.to_string(),
+                    });
+                }
+            }
+            tables.insert(Table::new(name, dice, entries)?)?;
+        }
+        Ok(tables)
+    }
+
+    /// Adds or replaces a table, unless it closes a cycle of references.
+    pub fn insert(&mut self, table: Table) -> DiroResult<()> {
+        let name = table.name.clone();
+        let previous = self.tables.insert(name.clone(), table);
+        if let Some(cycle) = self.find_cycle(&name, &mut vec![], &mut BTreeSet::new()) {
+            match previous {
+                Some(previous) => self.tables.insert(name, previous),
+                None => self.tables.remove(&name),
+            };
+            return Err(DiroError::TableCycle(cycle));
+        }
+        Ok(())
+    }
+
+    /// Follows the references from `name`, unknown tables are skipped.
+    fn find_cycle<'a>(
+        &'a self,
+        name: &'a str,
+        chain: &mut Vec<&'a str>,
+        done: &mut BTreeSet<&'a str>,
+    ) -> Option<String> {
+        if let Some(i) = chain.iter().position(|n| *n == name) {
+            let mut cycle = chain[i..].to_vec();
+            cycle.push(name);
+            return Some(cycle.join(" -> "));
+        }
+        if done.contains(name) {
+            return None;
+        }
+        let table = self.get(name)?;
+        chain.push(name);
+        for entry in &table.entries {
+            for reference in references(&entry.text) {
+                if let Some(cycle) = self.find_cycle(reference, chain, done) {
+                    return Some(cycle);
+                }
+            }
+        }
+        chain.pop();
+        done.insert(name);
+        None
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Table> {
+        self.tables.get(name)
+    }
+
+    pub fn roll(&self, name: &str) -> DiroResult<TableRoll> {
+        self.roll_with(name, &mut rand::thread_rng())
+    }
+
+    /// Like `roll`, but rolls with the given RNG.
+    pub fn roll_with<R: Rng + ?Sized>(&self, name: &str, rng: &mut R) -> DiroResult<TableRoll> {
+        self.roll_chain(name, &mut vec![], rng)
+    }
+
+    fn roll_chain<R: Rng + ?Sized>(
+        &self,
+        name: &str,
+        chain: &mut Vec<String>,
+        rng: &mut R,
+    ) -> DiroResult<TableRoll> {
+        if chain.iter().any(|n| n == name) {
+            chain.push(name.to_string());
+            return Err(DiroError::TableCycle(chain.join(" -> ")));
+        }
+        let table = self
+            .get(name)
+            .ok_or_else(|| DiroError::UnknownTable(name.to_string()))?;
+        let mut roll = table.dice.clone();
+        let result = roll.eval_with(rng)?;
+        let entry = table
+            .entry(result)
+            .ok_or_else(|| DiroError::NoTableEntry(name.to_string(), result))?;
+
+        chain.push(name.to_string());
+        let mut steps = vec![];
+        let text = self.expand(&entry.text, &mut steps, chain, rng)?;
+        chain.pop();
+        Ok(TableRoll {
+            table: name.to_string(),
+            roll,
+            text,
+            steps,
+        })
+    }
+
+    /// Rolls every reference, bracketed expression and dice word of `text`.
+    fn expand<R: Rng + ?Sized>(
+        &self,
+        text: &str,
+        steps: &mut Vec<TableStep>,
+        chain: &mut Vec<String>,
+        rng: &mut R,
+    ) -> DiroResult<String> {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(['{', '[']) {
+            let close = if rest[start..].starts_with('{') {
+                '}'
+            } else {
+                ']'
+            };
+            let Some(end) = rest[start..].find(close).map(|e| e + start) else {
+                break;
+            };
+            out.push_str(&self.expand_words(&rest[..start], steps, rng)?);
+            let inner = rest[start + 1..end].trim();
+            if close == '}' {
+                let roll = self.roll_chain(inner, chain, rng)?;
+                out.push_str(&roll.text);
+                steps.push(TableStep::Table(roll));
+            } else {
+                let mut ast = parse(inner)?;
+                out.push_str(&ast.eval_with(rng)?.to_string());
+                steps.push(TableStep::Dice(ast));
+            }
+            rest = &rest[end + 1..];
+        }
+        out.push_str(&self.expand_words(rest, steps, rng)?);
+        Ok(out)
+    }
+
+    fn expand_words<R: Rng + ?Sized>(
+        &self,
+        text: &str,
+        steps: &mut Vec<TableStep>,
+        rng: &mut R,
+    ) -> DiroResult<String> {
+        let mut words = vec![];
+        for word in text.split(' ') {
+            let trimmed = word.trim_end_matches([',', '.', ';', '!', '?']);
+            match dice_word(trimmed) {
+                Some(mut ast) => {
+                    let result = ast.eval_with(rng)?;
+                    words.push(format!("{}{}", result, &word[trimmed.len()..]));
+                    steps.push(TableStep::Dice(ast));
+                }
+                None => words.push(word.to_string()),
+            }
+        }
+        Ok(words.join(" "))
+    }
+}
+
+/// Cuts a `# comment` off the line.
+fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    for (i, c) in bytes.iter().enumerate() {
+        if *c == b'#'
+            && (i == 0
+                || (bytes[i - 1].is_ascii_whitespace()
+                    && bytes.get(i + 1).unwrap_or(&b' ').is_ascii_whitespace()))
+        {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// The names of the `{table}` references in an entry.
+fn references(text: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|e| e + start) else {
+            break;
+        };
+        names.push(rest[start + 1..end].trim());
+        rest = &rest[end + 1..];
+    }
+    names
+}
+
+/// `4` or `1-3`.
+fn parse_range(key: &str) -> Option<RangeInclusive<i32>> {
+    let (start, end) = match key.split_once('-') {
+        Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+        None => {
+            let i = key.trim().parse().ok()?;
+            (i, i)
+        }
+    };
+    (start <= end).then_some(start..=end)
+}
+
+/// A word such as `2d4` or `d20+1`, words without a digit are never dice.
+fn dice_word(word: &str) -> Option<DiroAst> {
+    if !word.contains(|c: char| c.is_ascii_digit()) || !word.contains(['d', 'D']) {
+        return None;
+    }
+    let ast = parse(word).ok()?;
+    fn has_dice(ast: &DiroAst) -> bool {
+        match ast {
+            DiroAst::Int(_) | DiroAst::Draw { .. } => false,
+            DiroAst::Dice(..) => true,
+            DiroAst::DyadicOP { lhs, rhs, .. } => has_dice(lhs) || has_dice(rhs),
+            DiroAst::Closed(ast) | DiroAst::Banded { ast, .. } => has_dice(ast),
+        }
+    }
+    has_dice(&ast).then_some(ast)
+}
+
+impl TableRoll {
+    /// Every roll of the chain, one per line, nested rolls indented.
+    pub fn detail(&self) -> DiroResult<String> {
+        self.render(&Plain)
+    }
+
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &R) -> DiroResult<String> {
+        let mut lines = vec![];
+        self.render_lines(renderer, 0, &mut lines)?;
+        Ok(lines.join("\n"))
+    }
+
+    fn render_lines<R: Renderer + ?Sized>(
+        &self,
+        renderer: &R,
+        depth: usize,
+        lines: &mut Vec<String>,
+    ) -> DiroResult<()> {
+        lines.push(format!(
+            "{}{}{}{}",
+            "  ".repeat(depth),
+            renderer.text(&format!("{}: ", self.table)),
+            self.roll.render_line(renderer)?,
+            renderer.text(&format!(" -> {}", self.text))
+        ));
+        for step in &self.steps {
+            match step {
+                TableStep::Dice(ast) => lines.push(format!(
+                    "{}{}",
+                    "  ".repeat(depth + 1),
+                    ast.render_line(renderer)?
+                )),
+                TableStep::Table(roll) => roll.render_lines(renderer, depth + 1, lines)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn tables_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let tables = Tables::parse(
+        "# encounters on the road
+[encounter]
+dice: 1d4+1
+1-3: goblin
+4: 2d4 wolves, hungry
+5: {loot}
+
+[loot]
+[2d6*10] gold
+a rusty sword # from the old fort
+Note: cursed
+",
+    )
+    .unwrap();
+    let loot = tables.get("loot").unwrap();
+    assert_eq!(loot.dice.expr(), "1D3");
+    assert_eq!(loot.entry(3).unwrap().text, "Note: cursed");
+    assert!(tables.get("encounter").unwrap().entry(1).is_some());
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..20 {
+        let roll = tables.roll_with("encounter", &mut rng).unwrap();
+        let result = roll.roll.calc().unwrap();
+        match result {
+            2..=3 => assert_eq!(roll.text, "goblin"),
+            4 => {
+                assert!(roll.text.ends_with(" wolves, hungry"));
+                assert!(matches!(roll.steps[..], [TableStep::Dice(_)]));
+            }
+            _ => {
+                let detail = roll.detail().unwrap();
+                let mut lines = detail.lines();
+                assert!(lines.next().unwrap().starts_with("encounter: 1D4+1 = "));
+                assert!(lines.next().unwrap().starts_with("  loot: 1D3 = "));
+            }
+        }
+    }
+
+    let roll = Table::new(
+        "gold",
+        Some(parse("1").unwrap()),
+        vec![Entry {
+            range: 1..=1,
+            text: "[2d6*10] gold".to_string(),
+        }],
+    )
+    .unwrap();
+    let mut tables = Tables::default();
+    tables.insert(roll).unwrap();
+    let roll = tables.roll_with("gold", &mut rng).unwrap();
+    let gold: i32 = roll.text.strip_suffix(" gold").unwrap().parse().unwrap();
+    assert_eq!(gold % 10, 0);
+    let detail = roll.detail().unwrap();
+    assert_eq!(detail.lines().count(), 2);
+    assert!(detail.lines().nth(1).unwrap().starts_with("  2D6*10 = "));
+}
+
+#[test]
+fn tables_errors() {
+    assert!(matches!(
+        Tables::parse("[a]\n{b}\n[b]\n1: x\n2: {a}\n"),
+        Err(DiroError::TableCycle(chain)) if chain == "b -> a -> b"
+    ));
+    assert!(matches!(
+        Tables::parse("[a]\n1: {a}\n"),
+        Err(DiroError::TableCycle(chain)) if chain == "a -> a"
+    ));
+    let mut tables = Tables::parse("[a]\n{b}\n[b]\nx\n").unwrap();
+    let b = Table::new(
+        "b",
+        None,
+        vec![Entry {
+            range: 1..=1,
+            text: "{a}".to_string(),
+        }],
+    )
+    .unwrap();
+    assert!(matches!(tables.insert(b), Err(DiroError::TableCycle(_))));
+    // the table it would have replaced is kept
+    assert_eq!(tables.roll("a").unwrap().text, "x");
+
+    assert!(matches!(
+        Tables::parse("[a]\n{missing}\n").unwrap().roll("a"),
+        Err(DiroError::UnknownTable(_))
+    ));
+    assert!(matches!(
+        Tables::parse("[a]\ndice: 4\n1-3: x\n").unwrap().roll("a"),
+        Err(DiroError::NoTableEntry(_, 4))
+    ));
+    assert!(matches!(
+        Tables::parse("1: x\n"),
+        Err(DiroError::InvalidTable(_))
+    ));
+    assert!(matches!(
+        Tables::parse("[a]\n1-3: x\n3: y\n"),
+        Err(DiroError::InvalidTable(_))
+    ));
+    assert!(matches!(
+        Tables::parse("[a]\n"),
+        Err(DiroError::InvalidTable(_))
+    ));
+    assert!(matches!(
+        Tables::parse("[a]\nx\n[{b}]\ny\n"),
+        Err(DiroError::InvalidTable(_))
+    ));
+    let tables = Tables::parse("[a]\n[1d4] goblins, [1d6]\n").unwrap();
+    assert_eq!(tables.get("a").unwrap().entries.len(), 1);
+}
+
+#[test]
+fn tables_format() {
+    let tables = Tables::parse(
+        "[rooms] # by number
+1: Room #3
+2: Room #4 # the last one
+#3: skipped
+[1d6]
+",
+    )
+    .unwrap();
+    let rooms = tables.get("rooms").unwrap();
+    assert_eq!(rooms.entries.len(), 3);
+    assert_eq!(rooms.entry(1).unwrap().text, "Room #3");
+    assert_eq!(rooms.entry(2).unwrap().text, "Room #4");
+    // a lone dice expression is an entry, not a header
+    assert_eq!(rooms.entry(3).unwrap().text, "[1d6]");
+    assert!(tables.get("1d6").is_none());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn tables_toml() {
+    let tables = Tables::from_toml(
+        r#"
+[encounter]
+dice = "1d6"
+"1-5" = "goblin"
+6 = "{loot}"
+
+[loot]
+1 = "2d4 gold"
+"#,
+    )
+    .unwrap();
+    assert_eq!(tables.get("encounter").unwrap().entries.len(), 2);
+    assert_eq!(tables.get("loot").unwrap().dice.expr(), "1D1");
+    assert!(matches!(
+        Tables::from_toml("[a]\nx = \"y\"\n"),
+        Err(DiroError::InvalidTable(_))
+    ));
+}
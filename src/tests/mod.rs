@@ -1,3 +1,11 @@
+use crate::cortex::CortexSelection;
+use crate::deck::{Card, Deck, Decks};
+use crate::fitd::{FitdKind, FitdOutcome};
+use crate::gurps::GurpsOutcome;
+use crate::ironsworn::IronswornHit;
+use crate::narrative::{NarrativeDie, Symbols};
+use crate::savage::SavageOutcome;
+use crate::shadowrun::ShadowrunRoll;
 use crate::*;
 
 #[test]
@@ -46,6 +54,61 @@ fn dices() {
             ),
         ),
         ("4f", DiroAst::Dice(Dice::FDice(4), None)),
+        ("4dF", DiroAst::Dice(Dice::FDice(4), None)),
+        ("dF", DiroAst::Dice(Dice::FDice(1), None)),
+        ("4dF.2", DiroAst::Dice(Dice::FDice(4), None)),
+        ("4dF.1", DiroAst::Dice(Dice::FDice1(4), None)),
+        (
+            "2g1y3p1k",
+            DiroAst::Dice(
+                Dice::Narrative(vec![
+                    (2, NarrativeDie::Ability),
+                    (1, NarrativeDie::Proficiency),
+                    (3, NarrativeDie::Difficulty),
+                    (1, NarrativeDie::Setback),
+                ]),
+                None,
+            ),
+        ),
+        (
+            "yz3s2g1",
+            DiroAst::Dice(
+                Dice::YearZero {
+                    base: 3,
+                    skill: 2,
+                    gear: 1,
+                },
+                None,
+            ),
+        ),
+        (
+            "fitd3",
+            DiroAst::Dice(
+                Dice::Fitd {
+                    count: 3,
+                    kind: FitdKind::Action,
+                },
+                None,
+            ),
+        ),
+        (
+            "fitd0r",
+            DiroAst::Dice(
+                Dice::Fitd {
+                    count: 0,
+                    kind: FitdKind::Resistance,
+                },
+                None,
+            ),
+        ),
+        (
+            "1d20adv",
+            DiroAst::Dice(Dice::advantage(1, 20, true).unwrap(), None),
+        ),
+        (
+            "d20dis",
+            DiroAst::Dice(Dice::advantage(1, 20, false).unwrap(), None),
+        ),
     ];
     for (input, expected) in data.iter() {
         let mut result = parse(input).unwrap();
@@ -59,3 +122,666 @@ fn dices() {
         )
     }
 }
+
+#[test]
+fn seeded() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut a = parse("4d6k3+1d100b2+3a8").unwrap();
+    let mut b = a.clone();
+    a.eval_with(&mut StdRng::seed_from_u64(42)).unwrap();
+    b.eval_with(&mut StdRng::seed_from_u64(42)).unwrap();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn bounds() {
+    let bounds = |source: &str| parse(source).unwrap().bounds();
+    assert_eq!(bounds("1d10%3").unwrap(), (0, 2));
+    assert_eq!(bounds("1d6%20").unwrap(), (1, 6));
+    assert_eq!(bounds("(1d6-4)%1d4").unwrap(), (-3, 2));
+    assert_eq!(bounds("10/(1d4-2)").unwrap(), (-10, 10));
+    assert_eq!(bounds("(1d6-3)/(1d3)").unwrap(), (-2, 3));
+    assert_eq!(bounds("1d4^2").unwrap(), (1, 16));
+    assert!(matches!(bounds("1d6%0"), Err(DiroError::ZeroDivision)));
+    assert!(matches!(bounds("10/(1d1-1)"), Err(DiroError::ZeroDivision)));
+    assert!(matches!(bounds("(1d4-2)^2"), Err(DiroError::Unbounded(_))));
+}
+
+#[test]
+fn advantage() {
+    assert!(parse("1d20advk1").is_err());
+    assert!(parse("0d20adv").is_err());
+
+    let d20 = |advantage, result: Vec<i32>| {
+        DiroAst::Dice(
+            Dice::advantage(1, 20, advantage).unwrap(),
+            Some(RollResult::Dice {
+                kq: if advantage { 1 } else { -1 },
+                result,
+            }),
+        )
+    };
+    let plus = |lhs, rhs| DiroAst::DyadicOP {
+        verb: Verb::Plus,
+        lhs: Box::new(lhs),
+        rhs: Box::new(DiroAst::Int(rhs)),
+    };
+
+    let ast = plus(d20(true, vec![20, 3]), 5);
+    assert_eq!(ast.expr(), "1D20ADV+5");
+    assert_eq!(ast.calc().unwrap(), 25);
+    let outcome = ast.outcome().unwrap();
+    assert_eq!(outcome.natural, Some(20));
+    assert!(outcome.critical && !outcome.fumble);
+
+    let ast = plus(d20(false, vec![20, 1]), 5);
+    assert_eq!(ast.calc().unwrap(), 6);
+    assert!(ast.outcome().unwrap().fumble);
+
+    let mut ast = parse("2d20k1+1d6").unwrap();
+    let (result, outcome) = ast.eval_outcome().unwrap();
+    let natural = outcome.natural.unwrap();
+    assert!((1..=20).contains(&natural));
+    assert!((natural + 1..=natural + 6).contains(&result));
+    assert_eq!(parse("3d20").unwrap().critical().expr(), "6D20");
+
+    let mut damage = parse("2d6k1+1d8+3").unwrap().critical();
+    assert_eq!(damage.expr(), "4D6K2+2D8+3");
+    let result = damage.eval().unwrap();
+    assert!((2 + 2 + 3..=12 + 16 + 3).contains(&result));
+}
+
+#[test]
+fn fate() {
+    assert!(parse("0dF").is_err());
+    assert!(parse("4dF.3").is_err());
+    assert_eq!(parse("4df").unwrap().expr(), "4DF");
+    assert_eq!(parse("4dF.1+2").unwrap().expr(), "4DF.1+2");
+
+    let ast = DiroAst::DyadicOP {
+        verb: Verb::Plus,
+        lhs: Box::new(DiroAst::Dice(
+            Dice::FDice(4),
+            Some(RollResult::FDice(vec![1, 0, -1, 1])),
+        )),
+        rhs: Box::new(DiroAst::Int(2)),
+    };
+    assert_eq!(ast.calc().unwrap(), 3);
+    assert_eq!(ast.ladder().unwrap(), "Good");
+    assert_eq!(
+        RollResult::FDice(vec![1, 0, -1, 1]).ladder_detail(),
+        "+0-+ (Average)"
+    );
+    for (value, ladder) in [
+        (-4, "Terrible-2"),
+        (-2, "Terrible"),
+        (0, "Mediocre"),
+        (8, "Legendary"),
+        (10, "Legendary+2"),
+    ] {
+        assert_eq!(fate_ladder(value), ladder);
+    }
+}
+
+#[test]
+fn narrative() {
+    assert_eq!(parse("2g1y3p1k").unwrap().expr(), "2G1Y3P1K");
+    assert!(parse("0g0p").is_err());
+
+    let face = |die, face| (die, Symbols::from_face(face));
+    let result = RollResult::Narrative(vec![
+        face(NarrativeDie::Ability, "SA"),
+        face(NarrativeDie::Proficiency, "R"),
+        face(NarrativeDie::Difficulty, "FT"),
+        face(NarrativeDie::Setback, ""),
+    ]);
+    assert_eq!(result.result(), 1);
+    assert_eq!(result.detail(), "g:SA y:R p:FT k:- (1 success, 1 triumph)");
+    assert_eq!(result.symbols().unwrap().net_advantage(), 0);
+
+    let force = RollResult::Narrative(vec![
+        face(NarrativeDie::Force, "LL"),
+        face(NarrativeDie::Force, "N"),
+    ]);
+    assert_eq!(force.result(), 1);
+    assert_eq!(
+        Dice::Narrative(vec![(2, NarrativeDie::Force)]).bounds(),
+        (-4, 4)
+    );
+    assert_eq!(
+        Dice::Narrative(vec![
+            (1, NarrativeDie::Proficiency),
+            (1, NarrativeDie::Difficulty)
+        ])
+        .bounds(),
+        (-2, 2)
+    );
+}
+
+#[test]
+fn year_zero() {
+    use crate::yearzero::YearZeroRoll;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    assert_eq!(parse("yz3s2g1").unwrap().expr(), "YZ3S2G1");
+    assert_eq!(parse("YZ4").unwrap().expr(), "YZ4");
+    assert!(parse("yz0").is_err());
+    assert_eq!(parse("yz200s100g50").unwrap().bounds().unwrap(), (0, 350));
+
+    let mut ast = parse("yz3s2").unwrap();
+    assert!(matches!(ast.push(), Err(DiroError::DiceNotRolled)));
+    let mut rng = StdRng::seed_from_u64(36);
+    ast.roll_with(&mut rng);
+    let before = ast.calc().unwrap();
+    ast.push_with(&mut rng).unwrap();
+    assert!(ast.calc().unwrap() >= before);
+    match &ast {
+        DiroAst::Dice(_, Some(RollResult::YearZero(rolls))) => assert_eq!(rolls.len(), 2),
+        _ => unreachable!(),
+    }
+    assert!(matches!(
+        parse("1d6").unwrap().push(),
+        Err(DiroError::NotPushable)
+    ));
+
+    let result = RollResult::YearZero(vec![YearZeroRoll {
+        base: vec![6, 1],
+        skill: vec![3],
+        gear: vec![],
+    }]);
+    assert_eq!(result.detail(), "[1]:B:6,1 S:3");
+    assert_eq!(result.result(), 1);
+}
+
+#[test]
+fn fitd() {
+    assert_eq!(parse("fitd2f").unwrap().expr(), "FITD2F");
+
+    let fitd = |kind, kq, result| RollResult::Fitd {
+        kind,
+        roll: Box::new(RollResult::Dice { kq, result }),
+    };
+    let r = fitd(FitdKind::Action, 1, vec![6, 3, 6]);
+    assert_eq!(r.fitd_outcome(), Some(FitdOutcome::Critical));
+    assert_eq!(r.result(), 6);
+    assert_eq!(r.detail(), "6+3+6 (critical)");
+
+    let r = fitd(FitdKind::Action, -1, vec![6, 6]);
+    assert_eq!(r.fitd_outcome(), Some(FitdOutcome::Success));
+
+    let r = fitd(FitdKind::Action, 1, vec![2, 5]);
+    assert_eq!(r.fitd_outcome(), Some(FitdOutcome::Partial));
+    assert_eq!(r.render(&Markdown), "~~2~~+5 (partial success)");
+
+    let r = fitd(FitdKind::Resistance, 1, vec![2, 4]);
+    assert_eq!(r.result(), 2);
+    assert_eq!(r.detail(), "2+4 (partial success, stress 2)");
+    assert_eq!(fitd(FitdKind::Resistance, 1, vec![6, 6]).result(), -1);
+
+    let r = fitd(FitdKind::Fortune, 1, vec![1, 3]);
+    assert_eq!(r.detail(), "1+3 (limited)");
+
+    let mut ast = parse("fitd0").unwrap();
+    let result = ast.eval().unwrap();
+    assert!((1..=6).contains(&result));
+}
+
+#[test]
+fn bands() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut ast = parse("pbta（2d6+1）").unwrap();
+    assert_eq!(ast.expr(), "PBTA(2D6+1)");
+    assert_eq!(ast.s_expr(), "(pbta (+ 2D6 1))");
+    assert_eq!(ast.bounds().unwrap(), (3, 13));
+
+    let mut rng = StdRng::seed_from_u64(7);
+    ast.roll_with(&mut rng);
+    let result = ast.calc().unwrap();
+    let tier = ast.outcome().unwrap().tier.unwrap();
+    assert_eq!(tier, Bands::pbta().tier(result).unwrap());
+    assert!(ast
+        .detail_expr()
+        .unwrap()
+        .ends_with(&format!(" [{}]", tier.name)));
+
+    let mut ast = parse("pbta12(12)+1").unwrap();
+    ast.roll();
+    assert_eq!(ast.detail_expr().unwrap(), "(12 [critical])+1");
+    assert_eq!(ast.outcome().unwrap().tier.unwrap().name, "critical");
+
+    let mut ast = DiroAst::Int(6).banded(Bands::pbta());
+    assert_eq!(ast.eval().unwrap(), 6);
+    assert_eq!(ast.detail_expr().unwrap(), "6 [miss]");
+    assert_eq!(parse("2d6").unwrap().tier().unwrap(), None);
+}
+
+#[test]
+fn savage() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    assert_eq!(parse("swd8+1").unwrap().expr(), "SWD8+1");
+    assert_eq!(parse("SWd12-2").unwrap().expr(), "SWD12-2");
+    assert_eq!(parse("swd8+1d6").unwrap().expr(), "SWD8+1D6");
+    assert!(matches!(parse("swd7"), Err(DiroError::InvalidTraitDie(7))));
+
+    let savage = |traits: Vec<i32>, wild: Vec<i32>, modifier| RollResult::Savage {
+        face: 8,
+        traits,
+        wild,
+        modifier,
+    };
+    let r = savage(vec![8, 8, 3], vec![4], 1);
+    assert_eq!(r.result(), 20);
+    assert_eq!(
+        r.savage_outcome(),
+        Some(SavageOutcome::Success { raises: 4 })
+    );
+    assert_eq!(r.detail(), "T:8+8+3 W:4 +1 (success, 4 raises)");
+    assert_eq!(
+        r.render(&Markdown),
+        "T:8+8+3 W:~~4~~ +1 (success, 4 raises)"
+    );
+
+    let r = savage(vec![2], vec![6, 1], 0);
+    assert_eq!(r.result(), 7);
+    assert_eq!(r.detail(), "T:2 W:6+1 (success)");
+
+    let r = savage(vec![1], vec![1], 2);
+    assert_eq!(r.result(), 3);
+    assert_eq!(r.savage_outcome(), Some(SavageOutcome::CriticalFailure));
+
+    let mut ast = parse("swd4").unwrap();
+    ast.eval_with(&mut StdRng::seed_from_u64(5)).unwrap();
+    let r = match &ast {
+        DiroAst::Dice(_, Some(r)) => r.clone(),
+        _ => unreachable!(),
+    };
+    if let RollResult::Savage { traits, wild, .. } = r {
+        assert!(traits[..traits.len() - 1].iter().all(|d| *d == 4));
+        assert!(wild[..wild.len() - 1].iter().all(|d| *d == 6));
+    }
+}
+
+#[test]
+fn roll_keep() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    assert_eq!(parse("7k3").unwrap().expr(), "7K3");
+    assert_eq!(parse("7K3+2").unwrap().expr(), "7K3+2");
+    assert_eq!(parse("2k3g").unwrap().expr(), "2K3G");
+    assert!(matches!(parse("3k4"), Err(DiroError::KQTooBig)));
+
+    let r = RollResult::RollKeep {
+        chains: vec![vec![10, 10, 2], vec![4], vec![10, 7], vec![1]],
+        kept: 2,
+        bonus: 0,
+        written: (4, 2),
+    };
+    assert_eq!(r.result(), 39);
+    assert_eq!(r.detail(), "(10+10+2) 4 (10+7) 1");
+    assert_eq!(r.render(&Markdown), "(10+10+2) ~~4~~ (10+7) ~~1~~");
+    // kept dice are marked like the ones kept by `k`, whatever they rolled
+    assert_eq!(r.render(&BBCode), "(10+10+2) [s]4[/s] (10+7) [s]1[/s]");
+
+    let mut ast = parse("14k9").unwrap();
+    assert_eq!(ast.bounds().unwrap(), (12, i32::MAX));
+    ast.roll_with(&mut StdRng::seed_from_u64(2));
+    assert!(ast.detail_expr().unwrap().ends_with(" +2 (→ 10K10+2)"));
+    match ast {
+        DiroAst::Dice(
+            _,
+            Some(RollResult::RollKeep {
+                chains,
+                kept,
+                bonus,
+                written,
+            }),
+        ) => {
+            assert_eq!((chains.len(), kept, bonus), (10, 10, 2));
+            assert_eq!(written, (14, 9));
+            for chain in chains {
+                assert!(chain[..chain.len() - 1].iter().all(|d| *d == 10));
+                assert_ne!(chain[chain.len() - 1], 10);
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn cortex() {
+    assert_eq!(parse("{d8,2d6,d10}").unwrap().expr(), "{D8,2D6,D10}");
+    assert_eq!(parse("{ d8，d6 }+1").unwrap().expr(), "{D8,D6}+1");
+    assert_eq!(parse("{d8,2d6,d10}").unwrap().bounds().unwrap(), (0, 18));
+    assert!(matches!(
+        parse("{d8,d20}"),
+        Err(DiroError::InvalidTraitDie(20))
+    ));
+
+    let r = RollResult::Cortex(vec![(8, 7), (6, 1), (6, 4), (10, 4), (12, 2)]);
+    assert_eq!(r.result(), 11);
+    let selection = r.cortex_selection().unwrap();
+    assert_eq!(
+        selection,
+        CortexSelection {
+            total: vec![0, 2],
+            effect: Some(4),
+            hitches: vec![1],
+        }
+    );
+    assert_eq!(
+        r.render(&Markdown),
+        "d8:7 d6:1 d6:4 d10:~~4~~ d12:**2** (effect d12, 1 hitch)"
+    );
+
+    let r = RollResult::Cortex(vec![(8, 1), (6, 1)]);
+    assert_eq!(r.result(), 0);
+    assert_eq!(r.detail(), "d8:1 d6:1 (effect d4, botch)");
+}
+
+#[test]
+fn gurps() {
+    assert_eq!(parse("gurps12").unwrap().expr(), "GURPS12");
+    assert_eq!(parse("GURPS12").unwrap().bounds().unwrap(), (-6, 9));
+
+    let gurps = |skill, result| RollResult::Gurps {
+        skill,
+        roll: Box::new(RollResult::Dice { kq: 0, result }),
+    };
+    let r = gurps(12, vec![4, 5, 2]);
+    assert_eq!(r.result(), 1);
+    assert_eq!(r.gurps_outcome(), Some(GurpsOutcome::Success));
+    assert_eq!(r.detail(), "4+5+2 (11 vs 12, success, margin +1)");
+
+    let r = gurps(15, vec![6, 5, 6]);
+    assert_eq!(r.result(), -2);
+    assert_eq!(r.detail(), "6+5+6 (17 vs 15, critical failure, margin -2)");
+    assert_eq!(
+        gurps(16, vec![6, 5, 6]).detail(),
+        "6+5+6 (17 vs 16, failure, margin -1)"
+    );
+    // 17 and 18 fail whatever the skill
+    let r = gurps(20, vec![6, 5, 6]);
+    assert_eq!(r.result(), -1);
+    assert_eq!(r.detail(), "6+5+6 (17 vs 20, failure, margin -1)");
+    let r = gurps(20, vec![6, 6, 6]);
+    assert_eq!(r.result(), -1);
+    assert_eq!(r.detail(), "6+6+6 (18 vs 20, critical failure, margin -1)");
+    assert_eq!(parse("gurps20").unwrap().bounds().unwrap(), (-1, 17));
+    assert_eq!(
+        gurps(16, vec![1, 2, 3]).gurps_outcome(),
+        Some(GurpsOutcome::CriticalSuccess)
+    );
+
+    let mut ast = parse("gurps14").unwrap();
+    ast.roll();
+    assert!(ast.detail_expr().unwrap().contains(" vs 14, "));
+}
+
+#[test]
+fn shadowrun() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    assert_eq!(parse("sr12").unwrap().expr(), "SR12");
+    assert_eq!(parse("12d6sr").unwrap().expr(), "SR12");
+    assert_eq!(parse("12D6SRe+2").unwrap().expr(), "SR12E+2");
+    assert_eq!(parse("sr12").unwrap().bounds().unwrap(), (0, 12));
+
+    let r = RollResult::Shadowrun(vec![ShadowrunRoll {
+        pool: 5,
+        edge: true,
+        rounds: vec![vec![6, 1, 1, 5, 1], vec![6], vec![3]],
+    }]);
+    assert_eq!(r.result(), 3);
+    let outcome = r.shadowrun_outcome().unwrap();
+    assert!(outcome.glitch && !outcome.critical_glitch);
+    assert_eq!(r.detail(), "[1]:6 1 1 5 1 [2]:6 [3]:3 (3 hits, glitch)");
+
+    let mut ast = parse("sr6").unwrap();
+    assert!(matches!(ast.push(), Err(DiroError::NotPushable)));
+    let mut rng = StdRng::seed_from_u64(11);
+    ast.roll_with(&mut rng);
+    let hits = ast.calc().unwrap();
+    ast.second_chance_with(&mut rng).unwrap();
+    assert!(ast.calc().unwrap() >= hits);
+    assert!(ast.detail_expr().unwrap().contains(" / [1]:"));
+    assert!(matches!(
+        ast.second_chance(),
+        Err(DiroError::NoSecondChance)
+    ));
+    assert!(matches!(
+        parse("3d6").unwrap().second_chance(),
+        Err(DiroError::NoSecondChance)
+    ));
+    let mut ast = parse("sr6e").unwrap();
+    ast.roll_with(&mut rng);
+    assert!(matches!(
+        ast.second_chance_with(&mut rng),
+        Err(DiroError::NoSecondChance)
+    ));
+    // the first pool isn't rerolled when the second can't be
+    let mut ast = parse("sr6+sr6e").unwrap();
+    ast.roll_with(&mut rng);
+    let rolled = ast.clone();
+    assert!(matches!(
+        ast.second_chance_with(&mut rng),
+        Err(DiroError::NoSecondChance)
+    ));
+    assert_eq!(ast, rolled);
+}
+
+#[test]
+fn v5() {
+    assert_eq!(parse("v5 7h2 diff3").unwrap().expr(), "V5 7H2 DIFF3");
+    assert_eq!(parse("V5 7").unwrap().expr(), "V5 7");
+    assert_eq!(parse("v5 5h1+1").unwrap().expr(), "V5 5H1+1");
+    assert_eq!(parse("v5 5").unwrap().bounds().unwrap(), (0, 9));
+    assert!(matches!(parse("v5 0h2"), Err(DiroError::NoDice)));
+
+    let r = RollResult::V5 {
+        regular: vec![10, 6, 3, 1, 2],
+        hunger: vec![10, 4],
+        difficulty: Some(3),
+    };
+    assert_eq!(r.result(), 5);
+    assert!(r.v5_outcome().unwrap().messy);
+    assert_eq!(
+        r.detail(),
+        "R:10,6,3,1,2 H:10,4 (5 successes vs 3, messy critical)"
+    );
+
+    let r = RollResult::V5 {
+        regular: vec![],
+        hunger: vec![1, 7],
+        difficulty: None,
+    };
+    assert_eq!(r.detail(), "H:1,7 (1 success, win)");
+    assert!(!r.v5_outcome().unwrap().bestial);
+
+    let mut ast = parse("v5 3h5").unwrap();
+    ast.roll();
+    match ast {
+        DiroAst::Dice(
+            _,
+            Some(RollResult::V5 {
+                regular, hunger, ..
+            }),
+        ) => {
+            assert_eq!((regular.len(), hunger.len()), (0, 3));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn ironsworn() {
+    assert_eq!(parse("is2").unwrap().expr(), "IS2");
+    assert_eq!(parse("is2+1+1").unwrap().expr(), "IS4");
+    assert_eq!(parse("ISP7").unwrap().expr(), "ISP7");
+    assert_eq!(parse("is2+1d6").unwrap().expr(), "IS2+1D6");
+
+    let mut r = RollResult::Ironsworn {
+        action: Some(4),
+        score: 2,
+        challenge: [3, 8],
+        momentum: None,
+    };
+    assert_eq!(r.result(), 1);
+    assert_eq!(r.ironsworn_outcome().unwrap().hit, IronswornHit::Weak);
+    assert_eq!(r.detail(), "4+2 vs 3,8 (weak hit)");
+    for momentum in [0, -3] {
+        assert!(matches!(
+            r.burn_momentum(momentum),
+            Err(DiroError::NoMomentum(m)) if m == momentum
+        ));
+    }
+    assert_eq!(r.detail(), "4+2 vs 3,8 (weak hit)");
+    r.burn_momentum(9).unwrap();
+    assert_eq!(r.result(), 2);
+    assert_eq!(r.render(&Markdown), "~~4~~+2 M:9 vs 3,8 (strong hit)");
+    // momentum never goes above +10
+    r.burn_momentum(15).unwrap();
+    assert_eq!(r.ironsworn_score(), Some(10));
+    assert_eq!(r.detail(), "4+2 M:10 vs 3,8 (strong hit)");
+
+    let mut r = RollResult::Ironsworn {
+        action: None,
+        score: 7,
+        challenge: [7, 7],
+        momentum: None,
+    };
+    assert_eq!(r.detail(), "P:7 vs 7,7 (miss, match)");
+    assert!(matches!(r.burn_momentum(9), Err(DiroError::NotBurnable)));
+
+    let mut ast = parse("is3").unwrap();
+    assert!(matches!(
+        ast.burn_momentum(10),
+        Err(DiroError::DiceNotRolled)
+    ));
+    ast.roll();
+    ast.burn_momentum(10).unwrap();
+    assert!(ast.detail_expr().unwrap().contains(" M:10 vs "));
+    assert!(matches!(
+        parse("isp3").unwrap().burn_momentum(10),
+        Err(DiroError::NotBurnable)
+    ));
+}
+
+#[test]
+fn deck() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut ast = parse("draw(init)+1").unwrap();
+    assert_eq!(ast.expr(), "DRAW(init)+1");
+    assert_eq!(ast.s_expr(), "(+ (draw init) 1)");
+    let err = ast.eval().unwrap_err();
+    assert!(matches!(&err, DiroError::CardNotDrawn(deck) if deck == "init"));
+    assert!(err.to_string().contains("eval_decks"));
+
+    let mut decks = Decks::default();
+    let mut rng = StdRng::seed_from_u64(9);
+    let mut init = Deck::standard("init");
+    init.shuffle_with(&mut rng);
+    let top = init.peek(1)[0].clone();
+    decks.insert(init);
+
+    assert_eq!(ast.eval_decks(&mut decks, &mut rng).unwrap(), top.value + 1);
+    assert_eq!(ast.detail_expr().unwrap(), format!("{}+1", top.value));
+    let mut ast = parse("draw（init）").unwrap();
+    ast.eval_decks(&mut decks, &mut rng).unwrap();
+    assert_eq!(ast.bounds().unwrap().0, ast.calc().unwrap());
+    assert_eq!(decks.get("init").unwrap().state().hand.len(), 2);
+
+    let mut empty = Decks::default();
+    empty.insert(Deck::new("one", vec![Card::new("x", 3)]));
+    let mut ast = parse("draw(one)+draw(one)").unwrap();
+    assert!(matches!(
+        ast.draw_decks(&mut empty),
+        Err(DiroError::DeckEmpty(_))
+    ));
+    assert!(matches!(
+        parse("draw(none)").unwrap().draw_decks(&mut empty),
+        Err(DiroError::UnknownDeck(_))
+    ));
+    // a failed draw leaves every deck as it was
+    assert_eq!(empty.get("one").unwrap().remaining(), 1);
+    empty.insert(Deck::new("none", vec![]));
+    let mut ast = parse("draw(one)+draw(none)").unwrap();
+    assert!(matches!(
+        ast.draw_decks(&mut empty),
+        Err(DiroError::DeckEmpty(_))
+    ));
+    assert_eq!(empty.get("one").unwrap().remaining(), 1);
+    assert!(matches!(ast.bounds(), Err(DiroError::CardNotDrawn(_))));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deck_serde() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut deck = Deck::tarot("fate");
+    deck.shuffle_with(&mut StdRng::seed_from_u64(4));
+    deck.draw().unwrap();
+    let state = serde_json::to_string(deck.state()).unwrap();
+    let mut restored = Deck::tarot("fate");
+    restored
+        .set_state(serde_json::from_str(&state).unwrap())
+        .unwrap();
+    assert_eq!(restored, deck);
+
+    let mut decks = Decks::default();
+    decks.insert(deck);
+    let json = serde_json::to_string(&decks).unwrap();
+    assert_eq!(serde_json::from_str::<Decks>(&json).unwrap(), decks);
+
+    // a state that doesn't hold every card exactly once is rejected
+    let mut value = serde_json::to_value(Deck::new("d", vec![Card::new("a", 1)])).unwrap();
+    value["state"]["draw_pile"] = serde_json::json!([5]);
+    assert!(serde_json::from_value::<Deck>(value.clone()).is_err());
+    value["state"]["draw_pile"] = serde_json::json!([0]);
+    value["state"]["hand"] = serde_json::json!([0]);
+    assert!(serde_json::from_value::<Deck>(value.clone()).is_err());
+    value["state"]["hand"] = serde_json::json!([]);
+    assert_eq!(
+        serde_json::from_value::<Deck>(value).unwrap().remaining(),
+        1
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn session_json_lines() {
+    use crate::session::Session;
+
+    let mut session = Session::with_seed(3);
+    session.roll_at("gm", "yz3s2g1", 1).unwrap();
+    session.roll_at("ann", "pbta(2d6+1)", 2).unwrap();
+    let jsonl = session.to_json_lines().unwrap();
+    assert_eq!(jsonl.lines().count(), 2);
+
+    let restored = Session::from_json_lines(&jsonl).unwrap();
+    assert_eq!(restored.entries(), session.entries());
+    assert!(restored.entries().iter().all(|e| e.verify().unwrap()));
+    assert!(matches!(
+        Session::from_json_lines("{}"),
+        Err(DiroError::InvalidLog(1, _))
+    ));
+}
+
+#[test]
+fn dice_fairness() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(50);
+    let reports = diagnostics::audit_presets_with(3000, &mut rng).unwrap();
+    assert_eq!(reports.len(), diagnostics::PRESETS.len());
+    for report in reports {
+        assert_eq!(report.samples, 3000);
+        assert_eq!(report.impossible, 0, "{}", report.describe());
+        assert!(report.degrees_of_freedom > 0, "{}", report.describe());
+        assert!(report.passed(0.001), "{}", report.describe());
+    }
+}
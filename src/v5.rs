@@ -0,0 +1,84 @@
+//! Vampire: the Masquerade 5th edition pools with hunger dice.
+
+/// Flags of a V5 roll, each pair of 10s is a critical worth 4 successes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct V5Outcome {
+    pub successes: i32,
+    /// The successes reach the difficulty.
+    pub win: bool,
+    /// A win with at least one pair of 10s.
+    pub critical: bool,
+    /// A critical with a 10 on a hunger die.
+    pub messy: bool,
+    /// A failure with a 1 on a hunger die.
+    pub bestial: bool,
+    /// No success at all.
+    pub total_failure: bool,
+}
+
+impl V5Outcome {
+    pub fn new(regular: &[i32], hunger: &[i32], difficulty: i32) -> Self {
+        let dice = || regular.iter().chain(hunger.iter());
+        let pairs = dice().filter(|d| **d == 10).count() as i32 / 2;
+        let successes = dice().filter(|d| **d >= 6).count() as i32 + pairs * 2;
+        let win = successes >= difficulty;
+        let critical = win && pairs > 0;
+        V5Outcome {
+            successes,
+            win,
+            critical,
+            messy: critical && hunger.contains(&10),
+            bestial: !win && hunger.contains(&1),
+            total_failure: successes == 0,
+        }
+    }
+
+    /// e.g. `5 successes vs 3, critical win`.
+    pub fn describe(&self, difficulty: Option<u8>) -> String {
+        let mut successes = match self.successes {
+            1 => "1 success".to_string(),
+            n => format!("{} successes", n),
+        };
+        if let Some(difficulty) = difficulty {
+            successes.push_str(&format!(" vs {}", difficulty));
+        }
+        let outcome = if self.messy {
+            "messy critical"
+        } else if self.critical {
+            "critical win"
+        } else if self.win {
+            "win"
+        } else if self.bestial {
+            "bestial failure"
+        } else if self.total_failure {
+            "total failure"
+        } else {
+            "failure"
+        };
+        format!("{}, {}", successes, outcome)
+    }
+}
+
+#[test]
+fn v5_test() {
+    let o = V5Outcome::new(&[10, 10, 6, 2], &[3], 3);
+    assert_eq!((o.successes, o.critical, o.messy), (5, true, false));
+    assert_eq!(o.describe(Some(3)), "5 successes vs 3, critical win");
+
+    let o = V5Outcome::new(&[10, 4], &[10, 1], 2);
+    assert_eq!((o.successes, o.messy, o.bestial), (4, true, false));
+    assert_eq!(o.describe(None), "4 successes, messy critical");
+
+    let o = V5Outcome::new(&[10, 10, 10], &[], 1);
+    assert_eq!(o.successes, 5);
+
+    let o = V5Outcome::new(&[7, 3], &[1, 2], 2);
+    assert_eq!(o.describe(None), "1 success, bestial failure");
+
+    let o = V5Outcome::new(&[2, 3], &[5], 1);
+    assert!(o.total_failure && !o.bestial);
+    assert_eq!(o.describe(None), "0 successes, total failure");
+
+    let o = V5Outcome::new(&[10, 10], &[], 5);
+    assert!(!o.win && !o.critical);
+}
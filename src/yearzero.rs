@@ -0,0 +1,83 @@
+//! Year Zero Engine pools of base, skill and gear d6s.
+
+use rand::Rng;
+
+/// One throw of a Year Zero pool, the first of `RollResult::YearZero`
+/// is the original roll and every following one a push.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct YearZeroRoll {
+    pub base: Vec<i32>,
+    pub skill: Vec<i32>,
+    pub gear: Vec<i32>,
+}
+
+impl YearZeroRoll {
+    pub(crate) fn roll<R: Rng + ?Sized>(base: u8, skill: u8, gear: u8, rng: &mut R) -> Self {
+        let mut d6 = |count| (0..count).map(|_| rng.gen_range(1..=6)).collect();
+        YearZeroRoll {
+            base: d6(base),
+            skill: d6(skill),
+            gear: d6(gear),
+        }
+    }
+
+    /// Rerolls every die except sixes and the ones on base and gear dice.
+    pub(crate) fn push<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        let mut reroll = |dice: &[i32], keep_ones: bool| {
+            dice.iter()
+                .map(|d| {
+                    if *d == 6 || (keep_ones && *d == 1) {
+                        *d
+                    } else {
+                        rng.gen_range(1..=6)
+                    }
+                })
+                .collect()
+        };
+        YearZeroRoll {
+            base: reroll(&self.base, true),
+            skill: reroll(&self.skill, false),
+            gear: reroll(&self.gear, true),
+        }
+    }
+
+    pub fn successes(&self) -> i32 {
+        self.base
+            .iter()
+            .chain(self.skill.iter())
+            .chain(self.gear.iter())
+            .filter(|d| **d == 6)
+            .count() as i32
+    }
+
+    /// Ones on base dice, which cost stress/damage once the roll is pushed.
+    pub fn damage(&self) -> i32 {
+        self.base.iter().filter(|d| **d == 1).count() as i32
+    }
+
+    /// Ones on gear dice, which break gear once the roll is pushed.
+    pub fn gear_damage(&self) -> i32 {
+        self.gear.iter().filter(|d| **d == 1).count() as i32
+    }
+}
+
+#[test]
+fn yearzero_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let roll = YearZeroRoll {
+        base: vec![6, 1, 3],
+        skill: vec![1, 6],
+        gear: vec![1, 2],
+    };
+    assert_eq!(roll.successes(), 2);
+    assert_eq!(roll.damage(), 1);
+    assert_eq!(roll.gear_damage(), 1);
+
+    let pushed = roll.push(&mut StdRng::seed_from_u64(3));
+    assert_eq!(&pushed.base[..2], &[6, 1]);
+    assert_eq!(pushed.skill[1], 6);
+    assert_eq!(pushed.gear[0], 1);
+    assert!(pushed.successes() >= 2);
+}